@@ -1,7 +1,88 @@
+//! A growable, index-addressed list. pill uses it for array-valued containers.
+
+use std::slice::Iter;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct List<T> {
+    items: Vec<T>,
+}
+
+impl<T> List<T> {
+    pub fn new() -> List<T> {
+        List { items: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    /// Replaces the item at `index`, handing back the old one. Out of bounds indices leave the list untouched.
+    pub fn set(&mut self, index: usize, item: T) -> Option<T> {
+        match self.items.get_mut(index) {
+            Some(slot) => Some(::std::mem::replace(slot, item)),
+            None => None,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.items.iter()
+    }
+}
+
+impl<T> From<Vec<T>> for List<T> {
+    fn from(items: Vec<T>) -> List<T> {
+        List { items }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.items.iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::List;
+
+    #[test]
+    fn push_and_pop() {
+        let mut list = List::new();
+        assert!(list.is_empty());
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+    }
+
     #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
+    fn get_and_set() {
+        let mut list = List::from(vec!["a", "b"]);
+        assert_eq!(list.get(1), Some(&"b"));
+        assert_eq!(list.get(2), None);
+        assert_eq!(list.set(0, "c"), Some("a"));
+        assert_eq!(list.set(5, "d"), None);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec!["c", "b"]);
     }
 }
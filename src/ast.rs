@@ -0,0 +1,89 @@
+pub mod ill {
+    use interpreter::ill::EnhancedFile;
+    use lexer::ill::Span;
+
+    /// One source file as written, before any name is looked up: what it uses, the registers it declares and its
+    /// instructions. Every node keeps the span it was read from.
+    #[derive(Debug, Clone, Default)]
+    pub struct Program {
+        pub file: EnhancedFile,
+        pub uses: Vec<Use>,
+        pub registers: Vec<RegisterDecl>,
+        pub instructions: Vec<InstructionDecl>,
+    }
+
+    /// `use "path.ill";` or `use std::name;`
+    #[derive(Debug, Clone)]
+    pub struct Use {
+        pub path: String,
+        pub span: Span,
+    }
+
+    /// One definition of a `+` line, like `!PI=3.14` or `^count`.
+    #[derive(Debug, Clone)]
+    pub struct RegisterDecl {
+        pub name: String,
+        pub span: Span,
+        pub is_constant: bool,
+        pub is_exported: bool,
+        pub initializer: Option<Expression>,
+    }
+
+    /// A register initializer. Parentheses only shape the tree, so they have no node of their own.
+    #[derive(Debug, Clone)]
+    pub enum Expression {
+        Number(f64, Span),
+        Str(String, Span),
+        Name(String, Span),
+        // unary `-`
+        Negate(Box<Expression>, Span),
+        // `+ - * / %`, spanning the operator
+        Binary(char, Box<Expression>, Box<Expression>, Span),
+    }
+
+    impl Expression {
+        pub fn span(&self) -> Span {
+            match *self {
+                Expression::Number(_, span) | Expression::Str(_, span) | Expression::Name(_, span) |
+                Expression::Negate(_, span) | Expression::Binary(_, _, _, span) => span,
+            }
+        }
+    }
+
+    /// `$name(parameters) { codes }`; `$$name` is the main instruction and `$^name` is exported from a module.
+    #[derive(Debug, Clone)]
+    pub struct InstructionDecl {
+        pub name: String,
+        // from the first `$` to the end of the name
+        pub span: Span,
+        pub is_main: bool,
+        pub is_exported: bool,
+        pub parameters: Vec<String>,
+        pub codes: Vec<Code>,
+    }
+
+    /// An OpCode and its arguments, as in `add amount total;`.
+    #[derive(Debug, Clone)]
+    pub struct Code {
+        pub name: String,
+        pub span: Span,
+        pub arguments: Vec<Argument>,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Argument {
+        pub value: ArgumentValue,
+        pub span: Span,
+    }
+
+    /// What an argument looks like; what it means (a literal, a container or an instruction) is only decided once it's
+    /// matched against its OpCode.
+    #[derive(Debug, Clone)]
+    pub enum ArgumentValue {
+        Number(f64),
+        Str(String),
+        Name(String),
+        // an instruction with its call-site arguments, like `inc(x 1)`
+        Call(String, Vec<Argument>),
+    }
+}
@@ -0,0 +1,948 @@
+pub mod ill {
+    use interpreter::ill::{AdvancedIllError, IllError, Value};
+    use std::cmp::Ordering;
+    use list::List;
+    use opcodes::ill::{OpCode, OpCodeHandler, ExecutionContext, ExpressionType, Flow, TRUE, FALSE};
+    use opcodes::ill::{prob_literal, container, register, variable, s_literal, inst_ref};
+
+    pub struct Mov;
+
+    impl OpCodeHandler for Mov {
+        fn signature(&self) -> OpCode {
+            OpCode::new("mov").expecting(prob_literal()).mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::ContainerReference(ref identifier) = code.arguments[1] {
+                    let val = ctx.value(code.head(), value)?;
+                    if ctx.debug {
+                        println!("Moved {} onto {}.", val, identifier);
+                    }
+                    ctx.container(code.head(), identifier)?.value = val;
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `add`, `sub`, `mul`, `div`, `pow`, `min` and `max`: `op value container` replaces the Number in the container
+    /// with `container op value`.
+    pub struct Arithmetic {
+        name: &'static str,
+        op: fn(f64, f64) -> f64,
+        divides: bool,
+    }
+
+    impl Arithmetic {
+        pub fn new(name: &'static str, op: fn(f64, f64) -> f64) -> Arithmetic {
+            Arithmetic { name, op, divides: false }
+        }
+
+        /// An operation that raises `DivisionByZero` rather than produce inf or NaN.
+        pub fn dividing(name: &'static str, op: fn(f64, f64) -> f64) -> Arithmetic {
+            Arithmetic { name, op, divides: true }
+        }
+    }
+
+    impl OpCodeHandler for Arithmetic {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(prob_literal()).mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::ContainerReference(ref variable) = code.arguments[1] {
+                    let val = ctx.number(code.head(), value)?;
+                    if self.divides && val == 0f64 {
+                        return Err(ctx.error(IllError::DivisionByZero(code.head()), code.head()));
+                    }
+                    let op = self.op;
+                    ctx.update_number(code.head(), variable, |n| op(n, val))?;
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `abs`, `flr` and `cel`: `op container` replaces the Number in the container with `op(container)`.
+    pub struct UnaryArithmetic {
+        name: &'static str,
+        op: fn(f64) -> f64,
+    }
+
+    impl UnaryArithmetic {
+        pub fn new(name: &'static str, op: fn(f64) -> f64) -> UnaryArithmetic {
+            UnaryArithmetic { name, op }
+        }
+    }
+
+    impl OpCodeHandler for UnaryArithmetic {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref variable) = code.arguments[0] {
+                ctx.update_number(code.head(), variable, self.op)?;
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub struct Mod;
+
+    impl OpCodeHandler for Mod {
+        fn signature(&self) -> OpCode {
+            OpCode::new("mod").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref t_for) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref by) = code.arguments[1] {
+                    if let ExpressionType::StringLiteral(ref identifier) = code.arguments[2] {
+                        let t_for_val = ctx.number(code.head(), t_for)?;
+                        let by_val = ctx.number(code.head(), by)?;
+                        if by_val == 0f64 {
+                            return Err(ctx.error(IllError::DivisionByZero(code.head()), code.head()));
+                        }
+                        ctx.declare(code.head(), identifier, t_for_val % by_val)?;
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `eq`, `lt`, `gt`, `gte` and `lte`: compare two values and store TRUE or FALSE in a new variable.
+    /// Numbers compare numerically and Strings lexicographically; values of different types never compare as TRUE.
+    pub struct Comparison {
+        name: &'static str,
+        test: fn(Ordering) -> bool,
+    }
+
+    impl Comparison {
+        pub fn new(name: &'static str, test: fn(Ordering) -> bool) -> Comparison {
+            Comparison { name, test }
+        }
+    }
+
+    impl OpCodeHandler for Comparison {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref t_for) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref by) = code.arguments[1] {
+                    if let ExpressionType::StringLiteral(ref identifier) = code.arguments[2] {
+                        let t_for_val = ctx.value(code.head(), t_for)?;
+                        let by_val = ctx.value(code.head(), by)?;
+                        let result = match t_for_val.compare(&by_val) {
+                            Some(ordering) if (self.test)(ordering) => TRUE,
+                            _ => FALSE,
+                        };
+                        ctx.declare(code.head(), identifier, result)?;
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `mak name value` declares a variable, `mkc name value` a constant one.
+    pub struct Mak {
+        name: &'static str,
+        constant: bool,
+    }
+
+    impl Mak {
+        pub fn new(name: &'static str, constant: bool) -> Mak {
+            Mak { name, constant }
+        }
+    }
+
+    impl OpCodeHandler for Mak {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(s_literal()).expecting(prob_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::StringLiteral(ref identifier) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref value) = code.arguments[1] {
+                    if identifier.eq_ignore_ascii_case("res") {
+                        let err = IllError::RegisterRedefinition(code.head(), identifier.clone(), Some(format!("default register {:?}", identifier)));
+                        return Err(ctx.error(err, code.head()));
+                    }
+                    let val = ctx.value(code.head(), value)?;
+                    if self.constant {
+                        ctx.declare_constant(code.head(), identifier, val)?;
+                    } else {
+                        ctx.declare(code.head(), identifier, val)?;
+                    }
+                    if ctx.debug {
+                        println!("Added variable {} => {}", identifier, value);
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub struct Neg;
+
+    impl OpCodeHandler for Neg {
+        fn signature(&self) -> OpCode {
+            OpCode::new("neg").mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref value) = code.arguments[0] {
+                ctx.update_number(code.head(), value, |n| if n == TRUE { FALSE } else { TRUE })?;
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub struct Del;
+
+    impl OpCodeHandler for Del {
+        fn signature(&self) -> OpCode {
+            OpCode::new("del").mutating(variable())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::VariableReference(ref name) = code.arguments[0] {
+                // every instruction hands back its `res`, so it can't go
+                if name.eq_ignore_ascii_case("res") {
+                    return Err(ctx.error(IllError::ImmutableRegister(code.head(), name.clone()), code.head()));
+                }
+                match ctx.scope.iter().position(|x| x.identifier == *name) {
+                    Some(pos) if ctx.scope[pos].is_constant => return Err(ctx.error(IllError::ImmutableRegister(code.head(), name.clone()), code.head())),
+                    Some(pos) => { ctx.scope.remove(pos); }
+                    None => return Err(ctx.error(IllError::NonExistentRegister(code.head(), name.clone()), code.head())),
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `dis` and `dsl`: print the value of a container, `dsl` with a trailing newline.
+    pub struct Display {
+        name: &'static str,
+        newline: bool,
+    }
+
+    impl Display {
+        pub fn new(name: &'static str, newline: bool) -> Display {
+            Display { name, newline }
+        }
+    }
+
+    impl OpCodeHandler for Display {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref identifier) = code.arguments[0] {
+                let value = ctx.lookup(code.head(), identifier)?.value.to_string();
+                if self.newline {
+                    ctx.output.write_line(&value);
+                } else {
+                    ctx.output.write(&value);
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `pt` and `ptl`: print a string literal, `ptl` with a trailing newline.
+    pub struct Print {
+        name: &'static str,
+        newline: bool,
+    }
+
+    impl Print {
+        pub fn new(name: &'static str, newline: bool) -> Print {
+            Print { name, newline }
+        }
+    }
+
+    impl OpCodeHandler for Print {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::StringLiteral(ref s) = code.arguments[0] {
+                if self.newline {
+                    ctx.output.write_line(s);
+                } else {
+                    ctx.output.write(s);
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub struct Do;
+
+    impl OpCodeHandler for Do {
+        fn signature(&self) -> OpCode {
+            OpCode::new("do").expecting(inst_ref())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::InstructionReference(ref inst, ref args) = code.arguments[0] {
+                let (_, flow) = ctx.call_with(code.head(), inst, args, &[])?;
+                return Ok(flow);
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub struct Dor;
+
+    impl OpCodeHandler for Dor {
+        fn signature(&self) -> OpCode {
+            OpCode::new("dor").expecting(inst_ref()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::InstructionReference(ref inst, ref args) = code.arguments[0] {
+                if let ExpressionType::StringLiteral(ref identifier) = code.arguments[1] {
+                    let (result, flow) = ctx.call_with(code.head(), inst, args, &[])?;
+                    ctx.declare(code.head(), identifier, result)?;
+                    return Ok(flow);
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `for name from through step body`: calls `body` once per value from `from` towards `through`, injecting the
+    /// current value as `name`. A negative `step` counts down. `for` stops before reaching `through`, `fore` includes it.
+    pub struct For {
+        name: &'static str,
+        inclusive: bool,
+    }
+
+    impl For {
+        pub fn exclusive(name: &'static str) -> For {
+            For { name, inclusive: false }
+        }
+
+        pub fn inclusive(name: &'static str) -> For {
+            For { name, inclusive: true }
+        }
+    }
+
+    impl OpCodeHandler for For {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(s_literal()).expecting(prob_literal()).expecting(prob_literal()).expecting(prob_literal()).expecting(inst_ref())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::StringLiteral(ref injected_var_name) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref from) = code.arguments[1] {
+                    if let ExpressionType::ProbableLiteral(ref through) = code.arguments[2] {
+                        if let ExpressionType::ProbableLiteral(ref step) = code.arguments[3] {
+                            if let ExpressionType::InstructionReference(ref inst, ref args) = code.arguments[4] {
+                                if ctx.clashes_with_register(injected_var_name) {
+                                    let err = IllError::RegisterRedefinition(code.head(), injected_var_name.clone(), Some(register().name()));
+                                    return Err(ctx.error(err, code.head()));
+                                }
+                                let from = ctx.number(code.head(), from)?;
+                                let through = ctx.number(code.head(), through)?;
+                                let step = ctx.number(code.head(), step)?;
+                                if step == 0f64 || !step.is_finite() {
+                                    return Err(ctx.error(IllError::InvalidLoopStep(code.head(), step), code.head()));
+                                }
+                                let in_range = |val: f64| match (step > 0f64, self.inclusive) {
+                                    (true, false) => val < through,
+                                    (true, true) => val <= through,
+                                    (false, false) => val > through,
+                                    (false, true) => val >= through,
+                                };
+                                // each value is worked out from the start, so steps like 0.1 don't pile up rounding errors
+                                let at = |count: u64| from + count as f64 * step;
+                                let mut count = 0u64;
+                                while in_range(at(count)) {
+                                    let val = at(count);
+                                    // far enough from 0, another step leaves the counter as it was
+                                    if at(count + 1) == val {
+                                        return Err(ctx.error(IllError::InvalidLoopStep(code.head(), step), code.head()));
+                                    }
+                                    match ctx.call_body(code.head(), inst, args, &[(injected_var_name, Value::Number(val))])?.1 {
+                                        Flow::Break => break,
+                                        Flow::Exit(status) => return Ok(Flow::Exit(status)),
+                                        _ => {}
+                                    }
+                                    count += 1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub struct If;
+
+    impl OpCodeHandler for If {
+        fn signature(&self) -> OpCode {
+            OpCode::new("if").expecting(inst_ref()).expecting(inst_ref()).expecting(inst_ref())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::InstructionReference(ref inst, ref args) = code.arguments[0] {
+                if let ExpressionType::InstructionReference(ref a_inst, ref a_args) = code.arguments[1] {
+                    if let ExpressionType::InstructionReference(ref b_inst, ref b_args) = code.arguments[2] {
+                        // a brk or cnt in either branch belongs to whatever loop this if sits in
+                        let (cond, flow) = ctx.call_with(code.head(), inst, args, &[])?;
+                        if let Flow::Exit(_) = flow {
+                            return Ok(flow);
+                        }
+                        let (value, flow) = if cond == Value::Number(TRUE) {
+                            ctx.call_branch(code.head(), a_inst, a_args)?
+                        } else {
+                            ctx.call_branch(code.head(), b_inst, b_args)?
+                        };
+                        // and a ret leaves the instruction this if is in, with the branch's result
+                        if flow == Flow::Return {
+                            ctx.container(code.head(), "res")?.value = value;
+                        }
+                        return Ok(flow);
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `while cond body`: calls `body` for as long as `cond` leaves TRUE in its `res`.
+    pub struct While;
+
+    impl OpCodeHandler for While {
+        fn signature(&self) -> OpCode {
+            OpCode::new("while").expecting(inst_ref()).expecting(inst_ref())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::InstructionReference(ref inst, ref args) = code.arguments[0] {
+                if let ExpressionType::InstructionReference(ref body, ref body_args) = code.arguments[1] {
+                    let mut iterations = 0;
+                    loop {
+                        match ctx.call_with(code.head(), inst, args, &[])? {
+                            (_, Flow::Exit(status)) => return Ok(Flow::Exit(status)),
+                            (cond, _) => if cond != Value::Number(TRUE) {
+                                break;
+                            },
+                        }
+                        if let Some(max) = ctx.max_iterations {
+                            if iterations >= max {
+                                return Err(ctx.error(IllError::IterationLimitExceeded(code.head(), max as i32), code.head()));
+                            }
+                        }
+                        match ctx.call_body(code.head(), body, body_args, &[])?.1 {
+                            Flow::Break => break,
+                            Flow::Exit(status) => return Ok(Flow::Exit(status)),
+                            _ => {}
+                        }
+                        iterations += 1;
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `brk` and `cnt`: leave the innermost loop, or skip straight to its next iteration.
+    pub struct LoopControl {
+        name: &'static str,
+        flow: Flow,
+    }
+
+    impl LoopControl {
+        pub fn new(name: &'static str, flow: Flow) -> LoopControl {
+            LoopControl { name, flow }
+        }
+    }
+
+    impl OpCodeHandler for LoopControl {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name)
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if !ctx.in_loop {
+                return Err(ctx.error(IllError::LoopControlOutsideLoop(code.head(), String::from(self.name)), code.head()));
+            }
+            Ok(self.flow.clone())
+        }
+    }
+
+    /// `ret [value]`: leaves the current instruction, optionally setting its `res` first. In a branch of an `if`, it leaves
+    /// the instruction the `if` is in too, handing it the same result.
+    pub struct Ret;
+
+    impl OpCodeHandler for Ret {
+        fn signature(&self) -> OpCode {
+            OpCode::new("ret").optionally(prob_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let Some(ExpressionType::ProbableLiteral(value)) = code.arguments.first() {
+                let val = ctx.value(code.head(), value)?;
+                ctx.container(code.head(), "res")?.value = val;
+            }
+            Ok(Flow::Return)
+        }
+    }
+
+    /// `exit [code]`: stops the program straight away, from however deep it is, with the given exit code (0 if left out),
+    /// a whole number from 0 to 255.
+    pub struct Exit;
+
+    impl OpCodeHandler for Exit {
+        fn signature(&self) -> OpCode {
+            OpCode::new("exit").optionally(prob_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            let status = match code.arguments.first() {
+                Some(ExpressionType::ProbableLiteral(value)) => ctx.number(code.head(), value)?,
+                _ => 0f64,
+            };
+            if !(0f64..=255f64).contains(&status) || status.fract() != 0f64 {
+                return Err(ctx.error(IllError::InvalidExitCode(code.head(), status), code.head()));
+            }
+            Ok(Flow::Exit(status as i32))
+        }
+    }
+
+    /// `cat a b name`: joins the text of two values into a new String variable.
+    pub struct Cat;
+
+    impl OpCodeHandler for Cat {
+        fn signature(&self) -> OpCode {
+            OpCode::new("cat").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref a) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref b) = code.arguments[1] {
+                    if let ExpressionType::StringLiteral(ref identifier) = code.arguments[2] {
+                        let joined = format!("{}{}", ctx.value(code.head(), a)?, ctx.value(code.head(), b)?);
+                        ctx.declare(code.head(), identifier, joined)?;
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `len v name`: the number of characters in a String, or of items in a List.
+    pub struct Len;
+
+    impl OpCodeHandler for Len {
+        fn signature(&self) -> OpCode {
+            OpCode::new("len").expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::StringLiteral(ref identifier) = code.arguments[1] {
+                    let len = match ctx.value(code.head(), value)? {
+                        Value::Str(s) => s.chars().count(),
+                        Value::List(list) => list.len(),
+                        other => return Err(ctx.error(IllError::TypeMismatch(code.head(), String::from("String or List"), other.type_name()), code.head())),
+                    };
+                    ctx.declare(code.head(), identifier, len as f64)?;
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `sbs s from to name`: the characters of a String from `from` up to, but not including, `to`.
+    pub struct Sbs;
+
+    impl OpCodeHandler for Sbs {
+        fn signature(&self) -> OpCode {
+            OpCode::new("sbs").expecting(prob_literal()).expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref from) = code.arguments[1] {
+                    if let ExpressionType::ProbableLiteral(ref to) = code.arguments[2] {
+                        if let ExpressionType::StringLiteral(ref identifier) = code.arguments[3] {
+                            let chars: Vec<char> = ctx.string(code.head(), value)?.chars().collect();
+                            let from = ctx.number(code.head(), from)?;
+                            let to = ctx.number(code.head(), to)?;
+                            // a bound may sit just past the last character, unlike a List index
+                            for &index in &[from, to] {
+                                if index < 0f64 || index > chars.len() as f64 || index.fract() != 0f64 {
+                                    return Err(ctx.error(IllError::IndexOutOfBounds(code.head(), index, chars.len()), code.head()));
+                                }
+                            }
+                            let (from, to) = (from as usize, to as usize);
+                            let part: String = if from < to { chars[from..to].iter().collect() } else { String::new() };
+                            ctx.declare(code.head(), identifier, part)?;
+                        }
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `num s name`: parses a String into a Number.
+    pub struct Num;
+
+    impl OpCodeHandler for Num {
+        fn signature(&self) -> OpCode {
+            OpCode::new("num").expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::StringLiteral(ref identifier) = code.arguments[1] {
+                    let number = match ctx.value(code.head(), value)? {
+                        Value::Number(n) => n,
+                        Value::Str(s) => match s.trim().parse::<f64>() {
+                            Ok(n) => n,
+                            Err(_) => return Err(ctx.error(IllError::NotANumber(code.head(), s), code.head())),
+                        },
+                        other => return Err(ctx.error(IllError::TypeMismatch(code.head(), String::from("String"), other.type_name()), code.head())),
+                    };
+                    ctx.declare(code.head(), identifier, number)?;
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `str v name`: the text of any value, as a String.
+    pub struct Str;
+
+    impl OpCodeHandler for Str {
+        fn signature(&self) -> OpCode {
+            OpCode::new("str").expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::StringLiteral(ref identifier) = code.arguments[1] {
+                    let text = ctx.value(code.head(), value)?.to_string();
+                    ctx.declare(code.head(), identifier, text)?;
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// Checks that `index` addresses one of `len` items.
+    fn list_index(ctx: &ExecutionContext, code: &OpCode, index: f64, len: usize) -> Result<usize, AdvancedIllError> {
+        if index < 0f64 || index >= len as f64 || index.fract() != 0f64 {
+            return Err(ctx.error(IllError::IndexOutOfBounds(code.head(), index, len), code.head()));
+        }
+        Ok(index as usize)
+    }
+
+    /// `lst name`: a new, empty List variable.
+    pub struct Lst;
+
+    impl OpCodeHandler for Lst {
+        fn signature(&self) -> OpCode {
+            OpCode::new("lst").expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::StringLiteral(ref identifier) = code.arguments[0] {
+                ctx.declare(code.head(), identifier, Value::List(List::new()))?;
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `psh value list`: appends a value to the end of a List.
+    pub struct Psh;
+
+    impl OpCodeHandler for Psh {
+        fn signature(&self) -> OpCode {
+            OpCode::new("psh").expecting(prob_literal()).mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::ContainerReference(ref identifier) = code.arguments[1] {
+                    let val = ctx.value(code.head(), value)?;
+                    ctx.list(code.head(), identifier)?.push(val);
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `pop list name`: removes the last item of a List and stores it in a new variable.
+    pub struct Pop;
+
+    impl OpCodeHandler for Pop {
+        fn signature(&self) -> OpCode {
+            OpCode::new("pop").mutating(container()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref list) = code.arguments[0] {
+                if let ExpressionType::StringLiteral(ref identifier) = code.arguments[1] {
+                    match ctx.list(code.head(), list)?.pop() {
+                        Some(val) => ctx.declare(code.head(), identifier, val)?,
+                        None => return Err(ctx.error(IllError::IndexOutOfBounds(code.head(), -1f64, 0), code.head())),
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `idx list index name`: copies the item at `index` into a new variable.
+    pub struct Idx;
+
+    impl OpCodeHandler for Idx {
+        fn signature(&self) -> OpCode {
+            OpCode::new("idx").expecting(container()).expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref list) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref index) = code.arguments[1] {
+                    if let ExpressionType::StringLiteral(ref identifier) = code.arguments[2] {
+                        let index = ctx.number(code.head(), index)?;
+                        let len = ctx.read_list(code.head(), list)?.len();
+                        let index = list_index(ctx, code, index, len)?;
+                        let val = ctx.read_list(code.head(), list)?.get(index).unwrap().clone();
+                        ctx.declare(code.head(), identifier, val)?;
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `set list index value`: replaces the item at `index`.
+    pub struct Set;
+
+    impl OpCodeHandler for Set {
+        fn signature(&self) -> OpCode {
+            OpCode::new("set").mutating(container()).expecting(prob_literal()).expecting(prob_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref list) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref index) = code.arguments[1] {
+                    if let ExpressionType::ProbableLiteral(ref value) = code.arguments[2] {
+                        let index = ctx.number(code.head(), index)?;
+                        let val = ctx.value(code.head(), value)?;
+                        let len = ctx.list(code.head(), list)?.len();
+                        let index = list_index(ctx, code, index, len)?;
+                        ctx.list(code.head(), list)?.set(index, val);
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `each name list body`: calls `body` once per item of a List, injecting the item as `name` the way `for`
+    /// injects its counter.
+    pub struct Each;
+
+    impl OpCodeHandler for Each {
+        fn signature(&self) -> OpCode {
+            OpCode::new("each").expecting(s_literal()).expecting(container()).expecting(inst_ref())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::StringLiteral(ref injected_var_name) = code.arguments[0] {
+                if let ExpressionType::ContainerReference(ref list) = code.arguments[1] {
+                    if let ExpressionType::InstructionReference(ref inst, ref args) = code.arguments[2] {
+                        if ctx.clashes_with_register(injected_var_name) {
+                            let err = IllError::RegisterRedefinition(code.head(), injected_var_name.clone(), Some(register().name()));
+                            return Err(ctx.error(err, code.head()));
+                        }
+                        // iterate over a snapshot, so the body is free to change the List
+                        let items = ctx.read_list(code.head(), list)?.clone();
+                        for item in &items {
+                            match ctx.call_body(code.head(), inst, args, &[(injected_var_name, item.clone())])?.1 {
+                                Flow::Break => break,
+                                Flow::Exit(status) => return Ok(Flow::Exit(status)),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    pub enum ReadKind {
+        Number,
+        Line,
+        Char,
+    }
+
+    /// `rdn`, `rdl` and `rdc`: read a Number, a line or a single character from the input into a container. Running out
+    /// of input leaves the container alone and sets the `eof` register to TRUE.
+    pub struct Read {
+        name: &'static str,
+        kind: ReadKind,
+    }
+
+    impl Read {
+        pub fn new(name: &'static str, kind: ReadKind) -> Read {
+            Read { name, kind }
+        }
+    }
+
+    impl OpCodeHandler for Read {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ContainerReference(ref identifier) = code.arguments[0] {
+                // make sure the target exists before consuming any input
+                ctx.container(code.head(), identifier)?;
+                let read = match self.kind {
+                    ReadKind::Number => match ctx.input.read_word() {
+                        Some(word) => match word.parse::<f64>() {
+                            Ok(n) => Some(Value::Number(n)),
+                            Err(_) => return Err(ctx.error(IllError::NotANumber(code.head(), word), code.head())),
+                        },
+                        None => None,
+                    },
+                    ReadKind::Line => ctx.input.read_line().map(Value::Str),
+                    ReadKind::Char => ctx.input.read_char().map(|x| Value::Str(x.to_string())),
+                };
+                ctx.set_eof(read.is_none());
+                if let Some(value) = read {
+                    ctx.container(code.head(), identifier)?.value = value;
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `opn path mode name`: opens a file for reading ("r"), writing ("w") or appending ("a") and stores its handle in
+    /// a new variable. Only paths inside an allowed directory can be opened.
+    pub struct Opn;
+
+    impl OpCodeHandler for Opn {
+        fn signature(&self) -> OpCode {
+            OpCode::new("opn").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref path) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref mode) = code.arguments[1] {
+                    if let ExpressionType::StringLiteral(ref identifier) = code.arguments[2] {
+                        let path = ctx.string(code.head(), path)?;
+                        let mode = ctx.string(code.head(), mode)?;
+                        let handle = match ctx.files.open(&path, &mode) {
+                            Ok(handle) => handle,
+                            Err(failure) => return Err(ctx.file_error(code.head(), &path, failure)),
+                        };
+                        ctx.declare(code.head(), identifier, Value::Handle(handle))?;
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `rdf handle container`: reads the next line of a file into a container, setting `eof` like the other reads.
+    pub struct Rdf;
+
+    impl OpCodeHandler for Rdf {
+        fn signature(&self) -> OpCode {
+            OpCode::new("rdf").expecting(prob_literal()).mutating(container())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref handle) = code.arguments[0] {
+                if let ExpressionType::ContainerReference(ref identifier) = code.arguments[1] {
+                    let handle = ctx.handle(code.head(), handle)?;
+                    ctx.container(code.head(), identifier)?;
+                    let read = match ctx.files.read_line(handle) {
+                        Ok(read) => read,
+                        Err(failure) => return Err(ctx.file_error(code.head(), &Value::Handle(handle).to_string(), failure)),
+                    };
+                    ctx.set_eof(read.is_none());
+                    if let Some(line) = read {
+                        ctx.container(code.head(), identifier)?.value = Value::Str(line);
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `wrt value handle` and `wrl value handle`: write the text of a value to a file, the latter followed by a newline.
+    pub struct FileWrite {
+        name: &'static str,
+        newline: bool,
+    }
+
+    impl FileWrite {
+        pub fn new(name: &'static str, newline: bool) -> FileWrite {
+            FileWrite { name, newline }
+        }
+    }
+
+    impl OpCodeHandler for FileWrite {
+        fn signature(&self) -> OpCode {
+            OpCode::new(self.name).expecting(prob_literal()).expecting(prob_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref value) = code.arguments[0] {
+                if let ExpressionType::ProbableLiteral(ref handle) = code.arguments[1] {
+                    let mut text = ctx.value(code.head(), value)?.to_string();
+                    if self.newline {
+                        text.push('\n');
+                    }
+                    let handle = ctx.handle(code.head(), handle)?;
+                    if let Err(failure) = ctx.files.write(handle, &text) {
+                        return Err(ctx.file_error(code.head(), &Value::Handle(handle).to_string(), failure));
+                    }
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+
+    /// `cls handle`: closes a file. Using its handle afterwards is an error.
+    pub struct Cls;
+
+    impl OpCodeHandler for Cls {
+        fn signature(&self) -> OpCode {
+            OpCode::new("cls").expecting(prob_literal())
+        }
+
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            if let ExpressionType::ProbableLiteral(ref handle) = code.arguments[0] {
+                let handle = ctx.handle(code.head(), handle)?;
+                if let Err(failure) = ctx.files.close(handle) {
+                    return Err(ctx.file_error(code.head(), &Value::Handle(handle).to_string(), failure));
+                }
+            }
+            Ok(Flow::Next)
+        }
+    }
+}
@@ -0,0 +1,363 @@
+pub mod ill {
+    use std::cmp;
+    use std::fmt;
+    use std::fmt::{Display, Formatter};
+    use std::io;
+
+    use termcolor::{Color, ColorSpec, WriteColor};
+
+    use interpreter::ill::{AdvancedIllError, EnhancedFile, ReadHead};
+    use lexer;
+    use lexer::ill::Span;
+
+    // a span covering more lines than this only shows its first two and its last
+    const MAX_SPAN_LINES: i32 = 4;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Severity {
+        Error,
+    }
+
+    impl Display for Severity {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match *self {
+                Severity::Error => write!(f, "error"),
+            }
+        }
+    }
+
+    /// Another place an error is about, like the first declaration of a register that's declared twice.
+    #[derive(Debug, Clone)]
+    pub struct Label {
+        pub file: EnhancedFile,
+        pub span: Span,
+        pub message: String,
+    }
+
+    /// Advice on fixing an error, optionally with the fix itself: `span` of the error's file replaced by the text.
+    #[derive(Debug, Clone)]
+    pub struct Help {
+        pub message: String,
+        pub fix: Option<(Span, String)>,
+    }
+
+    /// An error as it's shown to whoever ran the program, whether that's a person reading the terminal or an editor
+    /// reading `--error-format=json`: what kind of error it is, what went wrong and the stretch of source it's about,
+    /// along with any other places that explain it and advice on fixing it.
+    #[derive(Debug, Clone)]
+    pub struct Diagnostic {
+        pub severity: Severity,
+        // `IllError::name`
+        pub kind: String,
+        pub message: String,
+        pub file: EnhancedFile,
+        // missing for errors about no place in particular, like a missing main instruction
+        pub span: Option<Span>,
+        pub labels: Vec<Label>,
+        pub help: Vec<Help>,
+    }
+
+    impl Diagnostic {
+        pub fn new(err: &AdvancedIllError) -> Diagnostic {
+            Diagnostic {
+                severity: Severity::Error,
+                kind: err.error.name(),
+                message: err.error.get_actual_desc(),
+                file: err.file.clone(),
+                span: err.span.or_else(|| err.head.map(|head| token_at(&err.file, head))),
+                labels: err.labels.clone(),
+                help: err.help.clone(),
+            }
+        }
+
+        /// One JSON object on a single line, like
+        /// `{"severity":"error","kind":"Unknown OpCode","message":"...","file":"main.ill","span":{"line":4,"column":5,"end_line":4,"end_column":10},"labels":[],"help":[]}`.
+        /// `span` is `null` when there's nothing to point at. Each label has a `file`, `span` and `message`, and each help
+        /// note a `message` and a `fix` that's either `null` or the `span` to replace and the `text` to replace it with.
+        pub fn to_json(&self) -> String {
+            let span = self.span.map_or(String::from("null"), |span| json_span(&self.file, span));
+            let labels: Vec<String> = self.labels.iter().map(|x| {
+                format!("{{\"file\":{},\"span\":{},\"message\":{}}}", json_string(&x.file.filename), json_span(&x.file, x.span), json_string(&x.message))
+            }).collect();
+            let help: Vec<String> = self.help.iter().map(|x| {
+                let fix = x.fix.as_ref().map_or(String::from("null"), |&(span, ref text)| format!("{{\"span\":{},\"text\":{}}}", json_span(&self.file, span), json_string(text)));
+                format!("{{\"message\":{},\"fix\":{}}}", json_string(&x.message), fix)
+            }).collect();
+            format!("{{\"severity\":{},\"kind\":{},\"message\":{},\"file\":{},\"span\":{},\"labels\":[{}],\"help\":[{}]}}",
+                    json_string(&self.severity.to_string()), json_string(&self.kind), json_string(&self.message), json_string(&self.file.filename), span,
+                    labels.join(","), help.join(","))
+        }
+
+        /// Writes the diagnostic the way `pill` shows it in a terminal: the source around each span, underlined with `^`
+        /// for what's at fault and `-` for the places that explain it, then any help notes.
+        pub fn render<W: WriteColor>(&self, out: &mut W) -> io::Result<()> {
+            let span = match self.span {
+                Some(span) => span,
+                None => {
+                    // nothing to point at, e.g. a missing main instruction
+                    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+                    writeln!(out, "{}", self.kind)?;
+                    out.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                    writeln!(out, "{}: {}", self.file.filename, self.message)?;
+                    return self.render_help(out, 0);
+                }
+            };
+            let mut annotations = vec![Annotation { file: &self.file, span, message: &self.message, primary: true }];
+            annotations.extend(self.labels.iter().map(|x| Annotation { file: &x.file, span: x.span, message: &x.message, primary: false }));
+            let gutter = annotations.iter().map(|x| x.span.end_head(x.file).line).chain(self.help.iter().filter_map(|x| x.fix.as_ref().map(|y| y.0.line)))
+                .max().unwrap_or(1).to_string().len();
+
+            out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+            writeln!(out, "    {}{}", spaces(gutter), self.kind)?;
+            write!(out, "{}--> ", spaces(gutter))?;
+            out.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(out, "{}:{}:{}", self.file.filename, span.line, span.column)?;
+
+            // this file's annotations first, then those of every other file under a header of its own
+            let mut files: Vec<&EnhancedFile> = Vec::new();
+            for annotation in &annotations {
+                if !files.iter().any(|x| x.filename == annotation.file.filename) {
+                    files.push(annotation.file);
+                }
+            }
+            for (index, file) in files.into_iter().enumerate() {
+                let mut these: Vec<&Annotation> = annotations.iter().filter(|x| x.file.filename == file.filename).collect();
+                these.sort_by_key(|x| (x.span.line, x.span.column));
+                if index > 0 {
+                    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+                    write!(out, "{}::: ", spaces(gutter))?;
+                    out.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                    writeln!(out, "{}:{}:{}", file.filename, these[0].span.line, these[0].span.column)?;
+                }
+                gutter_line(out, gutter, None)?;
+                writeln!(out)?;
+                let mut printed = 0;
+                for annotation in these {
+                    annotation.render(out, gutter, &mut printed)?;
+                }
+            }
+            self.render_help(out, gutter)
+        }
+
+        fn render_help<W: WriteColor>(&self, out: &mut W, gutter: usize) -> io::Result<()> {
+            for help in &self.help {
+                out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+                write!(out, "{} = help: ", spaces(gutter))?;
+                out.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(out, "{}", help.message)?;
+                if let Some((span, ref text)) = help.fix {
+                    let (source, start) = (self.file.line(span.line), self.file.line_start(span.line));
+                    // only a fix within one line is shown inline
+                    if span.end > start + source.len() {
+                        continue;
+                    }
+                    let (before, after) = (&source[..span.start - start], &source[span.end - start..]);
+                    gutter_line(out, gutter, None)?;
+                    writeln!(out)?;
+                    gutter_line(out, gutter, Some(span.line))?;
+                    writeln!(out, " {}{}{}", before, text, after)?;
+                    gutter_line(out, gutter, None)?;
+                    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+                    writeln!(out, " {}{}", spaces(before.chars().count()), repeat('+', text.chars().count()))?;
+                }
+            }
+            out.set_color(ColorSpec::new().set_fg(Some(Color::White)))
+        }
+    }
+
+    struct Annotation<'a> {
+        file: &'a EnhancedFile,
+        span: Span,
+        message: &'a str,
+        primary: bool,
+    }
+
+    impl<'a> Annotation<'a> {
+        // the lines of the span and an underline under each of its first and last lines; `printed` is the last line
+        // already shown, so two annotations on one line share it
+        fn render<W: WriteColor>(&self, out: &mut W, gutter: usize, printed: &mut i32) -> io::Result<()> {
+            let marker = if self.primary { '^' } else { '-' };
+            let mut end = self.span.end_head(self.file);
+            // a span that runs up to a line break ends on the line before it
+            if end.line > self.span.line && end.column == 1 {
+                end.line -= 1;
+                end.column = self.file.line(end.line).chars().count() as i32 + 1;
+            }
+            let lines = end.line - self.span.line + 1;
+            for line in self.span.line..=end.line {
+                if lines > MAX_SPAN_LINES && line > self.span.line + 1 && line < end.line {
+                    if line == self.span.line + 2 {
+                        out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+                        writeln!(out, "...")?;
+                    }
+                    continue;
+                }
+                let source = self.file.line(line);
+                if line > *printed {
+                    gutter_line(out, gutter, Some(line))?;
+                    writeln!(out, " {}", source)?;
+                    *printed = line;
+                }
+                if line != self.span.line && line != end.line {
+                    continue;
+                }
+                let from = if line == self.span.line { self.span.column } else { 1 };
+                let to = if line == end.line { end.column } else { source.chars().count() as i32 + 1 };
+                gutter_line(out, gutter, None)?;
+                out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+                write!(out, " {}{}", spaces((from - 1) as usize), repeat(marker, cmp::max(to - from, 1) as usize))?;
+                out.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                if line == end.line {
+                    writeln!(out, " {}", self.message)?;
+                } else {
+                    writeln!(out)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    // the gutter in front of a source line, numbered, or blank in front of an underline
+    fn gutter_line<W: WriteColor>(out: &mut W, gutter: usize, line: Option<i32>) -> io::Result<()> {
+        out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+        match line {
+            Some(line) => write!(out, "{:>width$} |", line, width = gutter)?,
+            None => write!(out, "{} |", spaces(gutter))?,
+        }
+        out.set_color(ColorSpec::new().set_fg(Some(Color::White)))
+    }
+
+    fn spaces(count: usize) -> String {
+        repeat(' ', count)
+    }
+
+    fn repeat(ch: char, count: usize) -> String {
+        (0..count).map(|_| ch).collect()
+    }
+
+    // the token starting at `head`, which is what errors point at; anywhere else, like a character that starts no token,
+    // gets the one character there
+    fn token_at(file: &EnhancedFile, head: ReadHead) -> Span {
+        let line = file.line(head.line);
+        let start = file.line_start(head.line) + line.chars().take((head.column - 1) as usize).map(char::len_utf8).sum::<usize>();
+        match lexer::ill::token_at(file, start, head) {
+            Some(token) => token.span,
+            None => {
+                let end = start + file.content()[start..].chars().next().filter(|x| *x != '\n').map_or(0, char::len_utf8);
+                Span { start, end, line: head.line, column: head.column }
+            }
+        }
+    }
+
+    fn json_span(file: &EnhancedFile, span: Span) -> String {
+        let end = span.end_head(file);
+        format!("{{\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}}}", span.line, span.column, end.line, end.column)
+    }
+
+    fn json_string(text: &str) -> String {
+        let mut json = String::from("\"");
+        for ch in text.chars() {
+            match ch {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\r' => json.push_str("\\r"),
+                '\t' => json.push_str("\\t"),
+                ch if (ch as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", ch as u32)),
+                ch => json.push(ch),
+            }
+        }
+        json.push('"');
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termcolor::Buffer;
+
+    use interpreter::ill::{AdvancedIllError, EnhancedFile, IllError, Interpreter};
+    use lexer::ill::Span;
+    use opcodes::ill::default_opcodes;
+    use super::ill::Diagnostic;
+
+    fn first_error(program: &str) -> AdvancedIllError {
+        Interpreter::new(default_opcodes()).source("main.ill", program).run().errors.remove(0)
+    }
+
+    fn render(err: &AdvancedIllError) -> String {
+        let mut out = Buffer::no_color();
+        Diagnostic::new(err).render(&mut out).unwrap();
+        String::from_utf8(out.into_inner()).unwrap()
+    }
+
+    // a String argument running from line 2 to line 7
+    fn long_span() -> AdvancedIllError {
+        let file = EnhancedFile::new("main.ill", "+n;\nptl \"1\n2\n3\n4\n5\n6\";\n");
+        let span = Span { start: 8, end: 21, line: 2, column: 5 };
+        AdvancedIllError::new(IllError::DivisionByZero(span.head()), Some(span.head()), file).spanning(span)
+    }
+
+    #[test]
+    fn json_escapes_text() {
+        let file = EnhancedFile::new("say \"hi\"\t\u{1}.ill", "");
+        let json = Diagnostic::new(&AdvancedIllError::new(IllError::NoMainInstruction(), None, file)).to_json();
+        assert!(json.contains(r#""file":"say \"hi\"\t\u0001.ill""#), "{}", json);
+    }
+
+    #[test]
+    fn json_without_a_span() {
+        let json = Diagnostic::new(&first_error("+n;")).to_json();
+        assert_eq!(json, r#"{"severity":"error","kind":"No Main Instruction","message":"No Main instruction was found for any input files.","file":"main.ill","span":null,"labels":[],"help":[]}"#);
+    }
+
+    #[test]
+    fn json_with_a_label_and_a_fix() {
+        let json = Diagnostic::new(&first_error("+a;b;a;\n$$main() { }")).to_json();
+        assert_eq!(json, concat!(r#"{"severity":"error","kind":"Register Redefinition","message":"The Register named \"a\" already exists! ","#,
+                                 r#""file":"main.ill","span":{"line":1,"column":6,"end_line":1,"end_column":7},"#,
+                                 r#""labels":[{"file":"main.ill","span":{"line":1,"column":2,"end_line":1,"end_column":3},"message":"first declared here"}],"help":[]}"#));
+        let json = Diagnostic::new(&first_error("+a;\n$$main() { ptl a; }")).to_json();
+        assert!(json.ends_with(r#""help":[{"message":"quote it to use the text itself","fix":{"span":{"line":2,"column":16,"end_line":2,"end_column":17},"text":"\"a\""}}]}"#), "{}", json);
+    }
+
+    #[test]
+    fn renders_a_label() {
+        assert_eq!(render(&first_error("+a;b;a;\n$$main() { }")), "     Register Redefinition
+ --> main.ill:1:6
+  |
+1 | +a;b;a;
+  |  - first declared here
+  |      ^ The Register named \"a\" already exists! 
+");
+    }
+
+    #[test]
+    fn renders_a_fix() {
+        assert_eq!(render(&first_error("+a;\n$$main() {\n    ptl a;\n}")), "     Unescaped String Literal Misinterpreted
+ --> main.ill:3:9
+  |
+3 |     ptl a;
+  |         ^ Found an unescaped String literal that is also a container (register / variable). Try using \"a\".
+  = help: quote it to use the text itself
+  |
+3 |     ptl \"a\";
+  |         +++
+");
+    }
+
+    #[test]
+    fn renders_a_long_span() {
+        // only the first two lines and the last are shown
+        assert_eq!(render(&long_span()), "     Division By Zero
+ --> main.ill:2:5
+  |
+2 | ptl \"1
+  |     ^^
+3 | 2
+...
+7 | 6\";
+  | ^^ Attempted to divide by zero.
+");
+    }
+}
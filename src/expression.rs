@@ -0,0 +1,56 @@
+pub mod ill {
+    use ast::ill::Expression;
+    use interpreter::ill::{AdvancedIllError, EnhancedFile, IllError, ReadHead, Register, Value};
+
+    /// Evaluates a register initializer such as `2*(PI+1)` or `"v"+version` against the registers declared before it.
+    /// `+` joins the text of its operands as soon as either is a String; every other operator wants Numbers. Inside a
+    /// module, its own registers are found before global ones, and another module's are only found if they're among
+    /// the `exported` ones.
+    pub fn evaluate(file: &EnhancedFile, expr: &Expression, registers: &[Register], exported: &[String]) -> Result<Value, AdvancedIllError> {
+        let error = |err: IllError, head: ReadHead| AdvancedIllError::new(err, Some(head), file.clone());
+        let number = |value: Value, head: ReadHead| match value {
+            Value::Number(n) => Ok(n),
+            other => Err(error(IllError::TypeMismatch(head, String::from("Number"), other.type_name()), head)),
+        };
+        match *expr {
+            Expression::Number(n, _) => Ok(Value::Number(n)),
+            Expression::Str(ref text, _) => Ok(Value::Str(text.clone())),
+            Expression::Name(ref name, span) => {
+                if let Some(pos) = name.find("::") {
+                    let is_own = file.module.as_ref().is_some_and(|x| *x == name[..pos]);
+                    if !is_own && registers.iter().any(|x| x.identifier == *name) && !exported.contains(name) {
+                        return Err(error(IllError::PrivateItem(span.head(), name.clone()), span.head()));
+                    }
+                }
+                let own = file.module.as_ref().map(|x| format!("{}::{}", x, name));
+                let reg = own.and_then(|own| registers.iter().find(|x| x.identifier == own))
+                    .or_else(|| registers.iter().find(|x| x.identifier == *name));
+                match reg {
+                    Some(reg) => Ok(reg.value.clone()),
+                    None => Err(error(IllError::NonExistentRegister(span.head(), name.clone()), span.head())),
+                }
+            }
+            Expression::Negate(ref operand, span) => Ok(Value::Number(-number(evaluate(file, operand, registers, exported)?, span.head())?)),
+            Expression::Binary(op, ref lhs, ref rhs, span) => {
+                let head = span.head();
+                let (a, b) = (evaluate(file, lhs, registers, exported)?, evaluate(file, rhs, registers, exported)?);
+                Ok(match (op, a, b) {
+                    ('+', Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+                    ('+', a @ Value::Str(_), b) | ('+', a, b @ Value::Str(_)) => Value::Str(format!("{}{}", a, b)),
+                    (op, a, b) => {
+                        let (a, b) = (number(a, head)?, number(b, head)?);
+                        if (op == '/' || op == '%') && b == 0f64 {
+                            return Err(error(IllError::DivisionByZero(head), head));
+                        }
+                        Value::Number(match op {
+                            '-' => a - b,
+                            '*' => a * b,
+                            '/' => a / b,
+                            _ => a % b,
+                        })
+                    }
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+pub mod ill {
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::path::{Path, PathBuf};
+
+    enum OpenFile {
+        Reader(BufReader<File>),
+        Writer(File),
+    }
+
+    /// Why a file operation failed.
+    pub enum FileFailure {
+        // the path is outside every allowed directory
+        NotAllowed,
+        Io(String),
+    }
+
+    impl From<io::Error> for FileFailure {
+        fn from(err: io::Error) -> FileFailure {
+            FileFailure::Io(err.to_string())
+        }
+    }
+
+    /// The files a program has open, and the directories it may open them in. Nothing is allowed unless the
+    /// embedder (or `--allow-dir`) says so.
+    #[derive(Default)]
+    pub struct FileTable {
+        allowed: Vec<PathBuf>,
+        open: Vec<Option<OpenFile>>,
+    }
+
+    impl FileTable {
+        pub fn allow(&mut self, dir: &Path) {
+            self.allowed.push(dir.to_path_buf());
+        }
+
+        // where `path` really points, following symlinks and `..`, even if the file itself doesn't exist yet
+        fn resolve(&self, path: &Path) -> Result<PathBuf, FileFailure> {
+            if let Ok(path) = path.canonicalize() {
+                return Ok(path);
+            }
+            // something is there but leads nowhere, like a symlink to a file that doesn't exist yet: writing through it
+            // would create that file wherever the link points, so it's refused rather than checked as the link itself
+            if path.symlink_metadata().is_ok() {
+                return Err(FileFailure::NotAllowed);
+            }
+            let parent = match path.parent() {
+                Some(parent) if parent != Path::new("") => parent,
+                _ => Path::new("."),
+            };
+            let parent = match parent.canonicalize() {
+                Ok(parent) => parent,
+                // a directory that isn't there is only worth mentioning if it would have been allowed
+                Err(err) => {
+                    let existing = parent.ancestors().skip(1).find_map(|x| x.canonicalize().ok());
+                    let existing = existing.or_else(|| Path::new(".").canonicalize().ok());
+                    return Err(match existing {
+                        Some(ref dir) if self.is_allowed(dir) => FileFailure::from(err),
+                        _ => FileFailure::NotAllowed,
+                    });
+                }
+            };
+            match path.file_name() {
+                Some(name) => Ok(parent.join(name)),
+                None => Err(FileFailure::Io(String::from("not a file path"))),
+            }
+        }
+
+        fn is_allowed(&self, path: &Path) -> bool {
+            self.allowed.iter().filter_map(|x| x.canonicalize().ok()).any(|dir| path.starts_with(dir))
+        }
+
+        /// Opens `path` for reading ("r"), writing ("w") or appending ("a"), handing back its handle.
+        pub fn open(&mut self, path: &str, mode: &str) -> Result<usize, FileFailure> {
+            let resolved = self.resolve(Path::new(path))?;
+            if !self.is_allowed(&resolved) {
+                return Err(FileFailure::NotAllowed);
+            }
+            let file = match mode {
+                "r" => OpenFile::Reader(BufReader::new(File::open(&resolved)?)),
+                "w" => OpenFile::Writer(File::create(&resolved)?),
+                "a" => OpenFile::Writer(OpenOptions::new().append(true).create(true).open(&resolved)?),
+                _ => return Err(FileFailure::Io(format!("unknown mode {:?}, expected \"r\", \"w\" or \"a\"", mode))),
+            };
+            self.open.push(Some(file));
+            Ok(self.open.len() - 1)
+        }
+
+        /// The next line of a file opened for reading, without its line ending; `None` at the end of the file.
+        pub fn read_line(&mut self, handle: usize) -> Result<Option<String>, FileFailure> {
+            match self.open.get_mut(handle) {
+                Some(&mut Some(OpenFile::Reader(ref mut reader))) => {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line)? == 0 {
+                        return Ok(None);
+                    }
+                    if line.ends_with('\n') {
+                        line.pop();
+                        if line.ends_with('\r') {
+                            line.pop();
+                        }
+                    }
+                    Ok(Some(line))
+                }
+                Some(&mut Some(OpenFile::Writer(_))) => Err(FileFailure::Io(String::from("the file is not open for reading"))),
+                _ => Err(FileFailure::Io(String::from("the file is already closed"))),
+            }
+        }
+
+        pub fn write(&mut self, handle: usize, text: &str) -> Result<(), FileFailure> {
+            match self.open.get_mut(handle) {
+                Some(&mut Some(OpenFile::Writer(ref mut file))) => Ok(file.write_all(text.as_bytes())?),
+                Some(&mut Some(OpenFile::Reader(_))) => Err(FileFailure::Io(String::from("the file is not open for writing"))),
+                _ => Err(FileFailure::Io(String::from("the file is already closed"))),
+            }
+        }
+
+        pub fn close(&mut self, handle: usize) -> Result<(), FileFailure> {
+            match self.open.get_mut(handle) {
+                Some(slot) if slot.is_some() => {
+                    *slot = None;
+                    Ok(())
+                }
+                _ => Err(FileFailure::Io(String::from("the file is already closed"))),
+            }
+        }
+    }
+}
@@ -1,77 +1,293 @@
 pub mod ill {
-    use std::fs::File;
-    use std::io::Read;
-    use std::iter::Peekable;
-    use std::str::Chars;
+    use std::io::{self, BufRead, BufReader, Cursor, Write};
+    use std::collections::VecDeque;
     use std::error::Error;
     use std::fmt;
     use std::fmt::{Display, Formatter};
-    use std::ops::Sub;
-
-    use opcodes::ill::OpCode;
+    use std::ops::{Deref, Sub};
+    use std::cmp::Ordering;
+    use std::path::{Path, PathBuf};
+    use std::fs;
+    use std::panic;
+    use std::sync::Arc;
+    use std::thread;
+
+    use opcodes::ill::{OpCode, OpCodeRegistry, ExecutionContext, Flow, FALSE};
     use opcodes::ill::ExpressionType;
     use opcodes::ill::s_literal;
 
-    use pcre::Pcre;
+    use files::ill::FileTable;
+    use expression::ill::evaluate;
+    use ast::ill::{ArgumentValue, Code, Program};
+    use parser::ill::{parse, INST_DEF, STD_PREFIX};
+    use stdlib::ill as stdlib;
+    use lexer::ill::Span;
+    use diagnostic::ill::{Help, Label};
+    use list::List;
     use either::Either;
     use time::Duration;
 
-    use NamedFile;
     use self::IllError::*;
 
-    const NEWLINE: char = '\n';
-    const REGISTER_DEF: char = '+';
-    const DEF_END: char = ';';
+    // modules
+    const NAMESPACE_SEPARATOR: &str = "::";
+
+    pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+    // the most Rust stack one nested instruction call may take (in a debug build, with an `each` or `for` in between),
+    // and what the interpreter needs on top of all of them
+    const STACK_PER_CALL: usize = 64 * 1024;
+    const BASE_STACK: usize = 4 * 1024 * 1024;
 
+    /// The exit code of a run that failed before its main instruction started.
+    pub const EXIT_PARSE_ERROR: i32 = 2;
 
-    // instructions
-    const INST_DEF: char = '$';
+    /// The exit code of a run that failed while executing.
+    pub const EXIT_RUNTIME_ERROR: i32 = 1;
 
-    const INST_PARAM_BEGIN: char = '(';
-    const INST_PARAM_END: char = ')';
+    /// Set to TRUE once a read runs out of input.
+    pub const EOF_REGISTER: &str = "eof";
 
-    const INST_CODES_BEGIN: char = '{';
-    const INST_CODES_END: char = '}';
+    /// How many program arguments were passed (read-only).
+    pub const ARGC_REGISTER: &str = "argc";
 
-    // comments
-    const COMMENT_SINGLE_LINE: char = '>';
+    /// The program arguments themselves, as a List of Strings (read-only).
+    pub const ARGV_REGISTER: &str = "argv";
 
     #[derive(Default, Debug, Clone)]
     pub struct Register {
         pub identifier: String,
-        pub value: f64,
+        pub value: Value,
         pub is_variable: bool,
+        // read-only, like `argc`, `argv` and anything declared with `+!` or `mkc`
+        pub is_constant: bool,
     }
 
-    #[derive(Debug)]
-    pub struct EnhancedFile {
-        file: File,
+    /// What a register or variable holds.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Value {
+        Number(f64),
+        Str(String),
+        List(List<Value>),
+        // an open file, by its slot in the `FileTable`
+        Handle(usize),
+    }
+
+    impl Value {
+        pub fn type_name(&self) -> String {
+            String::from(match *self {
+                Value::Number(_) => "Number",
+                Value::Str(_) => "String",
+                Value::List(_) => "List",
+                Value::Handle(_) => "File Handle",
+            })
+        }
+
+        /// Orders two values of the same type; values of different types (and NaN) are unordered.
+        /// Lists are only ever equal or unordered.
+        pub fn compare(&self, other: &Value) -> Option<Ordering> {
+            match (self, other) {
+                (&Value::Number(a), &Value::Number(b)) => a.partial_cmp(&b),
+                (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+                (Value::List(a), Value::List(b)) if a == b => Some(Ordering::Equal),
+                (&Value::Handle(a), &Value::Handle(b)) if a == b => Some(Ordering::Equal),
+                _ => None,
+            }
+        }
+    }
+
+    impl Default for Value {
+        fn default() -> Value {
+            Value::Number(0f64)
+        }
+    }
+
+    impl From<f64> for Value {
+        fn from(number: f64) -> Value {
+            Value::Number(number)
+        }
+    }
+
+    impl From<String> for Value {
+        fn from(string: String) -> Value {
+            Value::Str(string)
+        }
+    }
+
+    impl Display for Value {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match *self {
+                Value::Number(n) => write!(f, "{}", n),
+                Value::Str(ref s) => write!(f, "{}", s),
+                Value::List(ref list) => {
+                    let items: Vec<String> = list.iter().map(|x| x.to_string()).collect();
+                    write!(f, "[{}]", items.join(", "))
+                }
+                Value::Handle(handle) => write!(f, "<file {}>", handle),
+            }
+        }
+    }
+
+    /// A source file. Its text is stored once and shared, so cloning one is cheap: every instruction, context and
+    /// error that belongs to a file holds on to it this way.
+    #[derive(Default, Debug, Clone)]
+    pub struct EnhancedFile(Arc<SourceFile>);
+
+    #[derive(Default, Debug)]
+    pub struct SourceFile {
         pub filename: String,
         content: String,
+        // the byte offset each line starts at, so a line can be found without reading up to it
+        line_starts: Vec<usize>,
+        // the namespace of a file loaded through `use`, `None` for the sources handed to the interpreter
+        pub module: Option<String>,
     }
 
     impl EnhancedFile {
-        pub fn unsafe_clone(&self) -> EnhancedFile { self.try_clone().unwrap() }
-        fn try_clone(&self) -> Option<EnhancedFile> {
-            let f_copy = self.file.try_clone();
-            if f_copy.is_ok() {
-                return Some(EnhancedFile {
-                    file: f_copy.ok().unwrap(),
-                    filename: self.filename.clone(),
-                    content: self.content.clone(),
-                });
+        pub fn new(filename: &str, content: &str) -> EnhancedFile {
+            EnhancedFile(Arc::new(SourceFile::read(filename, content, None)))
+        }
+
+        /// A file loaded through `use`, whose names live in the namespace `module`.
+        pub fn module(filename: &str, content: &str, module: &str) -> EnhancedFile {
+            EnhancedFile(Arc::new(SourceFile::read(filename, content, Some(String::from(module)))))
+        }
+    }
+
+    impl Deref for EnhancedFile {
+        type Target = SourceFile;
+
+        fn deref(&self) -> &SourceFile {
+            &self.0
+        }
+    }
+
+    impl SourceFile {
+        fn read(filename: &str, content: &str, module: Option<String>) -> SourceFile {
+            let breaks = content.match_indices('\n').map(|(pos, _)| pos + 1);
+            SourceFile {
+                filename: String::from(filename),
+                content: String::from(content),
+                line_starts: Some(0).into_iter().chain(breaks).collect(),
+                module,
             }
-            None
+        }
+
+        pub fn content(&self) -> &str {
+            &self.content
+        }
+
+        /// The byte offset `line` (counted from 1) starts at; past the last line, the end of the file.
+        pub fn line_start(&self, line: i32) -> usize {
+            self.line_starts.get((line - 1) as usize).cloned().unwrap_or(self.content.len())
+        }
+
+        /// The text of `line`, without its line ending. The end of a file that ends in a newline is on an empty line
+        /// of its own.
+        pub fn line(&self, line: i32) -> &str {
+            let start = self.line_start(line);
+            let end = self.line_starts.get(line as usize).cloned().unwrap_or(self.content.len());
+            let text = &self.content[start..end];
+            text.strip_suffix('\n').map_or(text, |x| x.strip_suffix('\r').unwrap_or(x))
         }
     }
 
-    impl Clone for EnhancedFile {
-        fn clone(&self) -> EnhancedFile {
-            EnhancedFile {
-                filename: self.filename.clone(),
-                content: self.content.clone(),
-                file: self.file.try_clone().expect("Failed to copy file..."),
+    /// Everything the program prints: captured, or written straight to stdout as it happens.
+    #[derive(Default, Debug)]
+    pub struct Output {
+        pub buffer: String,
+        stream: bool,
+    }
+
+    impl Output {
+        pub fn write(&mut self, text: &str) {
+            if self.stream {
+                print!("{}", text);
+                let _ = ::std::io::stdout().flush();
+            } else {
+                self.buffer.push_str(text);
+            }
+        }
+
+        pub fn write_line(&mut self, text: &str) {
+            self.write(text);
+            self.write("\n");
+        }
+    }
+
+    /// Where the program reads from; stdin unless the embedder supplied something else.
+    pub struct Input {
+        reader: Box<dyn BufRead + Send>,
+        // the rest of the line most recently read from `reader`
+        buffer: VecDeque<char>,
+    }
+
+    impl Input {
+        pub fn from_reader<R: BufRead + Send + 'static>(reader: R) -> Input {
+            Input { reader: Box::new(reader), buffer: VecDeque::new() }
+        }
+
+        // pulls the next line into the buffer, false once there's nothing left
+        fn fill(&mut self) -> bool {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(n) if n > 0 => {
+                    self.buffer.extend(line.chars());
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        pub fn read_char(&mut self) -> Option<char> {
+            if self.buffer.is_empty() && !self.fill() {
+                return None;
+            }
+            self.buffer.pop_front()
+        }
+
+        /// The rest of the current line, without its line ending.
+        pub fn read_line(&mut self) -> Option<String> {
+            if self.buffer.is_empty() && !self.fill() {
+                return None;
+            }
+            let mut line: String = self.buffer.drain(..).collect();
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            Some(line)
+        }
+
+        /// The next run of non-whitespace characters, skipping any whitespace (and line breaks) before it.
+        pub fn read_word(&mut self) -> Option<String> {
+            loop {
+                while self.buffer.front().is_some_and(|x| x.is_whitespace()) {
+                    self.buffer.pop_front();
+                }
+                if !self.buffer.is_empty() {
+                    break;
+                }
+                if !self.fill() {
+                    return None;
+                }
+            }
+            let mut word = String::new();
+            while let Some(&ch) = self.buffer.front() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                word.push(ch);
+                self.buffer.pop_front();
             }
+            Some(word)
+        }
+    }
+
+    impl Default for Input {
+        fn default() -> Input {
+            Input::from_reader(BufReader::new(io::stdin()))
         }
     }
 
@@ -82,26 +298,54 @@ pub mod ill {
     }
 
     pub struct AdvancedIllError {
-        pub error: IllError,
+        // boxed so every `Result` that can fail with one stays small
+        pub error: Box<IllError>,
         pub head: Option<ReadHead>,
         pub file: EnhancedFile,
+        // exactly what's at fault, when that's more than the token at `head`
+        pub span: Option<Span>,
+        // other places that explain the error, like where a redefined register was first declared
+        pub labels: Vec<Label>,
+        pub help: Vec<Help>,
     }
 
     impl AdvancedIllError {
         pub fn get_error_portion(&self) -> Option<String> {
-            if self.head.is_none() { return None; }
-            let text = self.file.content.clone();
-            let line = text.lines().nth((self.head.unwrap().line - 1) as usize).unwrap();
-            return Some(line.to_string());
+            self.head.map(|head| self.file.line(head.line).to_string())
         }
 
         pub fn new(err: IllError, head: Option<ReadHead>, file: EnhancedFile) -> AdvancedIllError {
             AdvancedIllError {
-                error: err,
+                error: Box::new(err),
                 head,
                 file,
+                span: None,
+                labels: Vec::new(),
+                help: Vec::new(),
             }
         }
+
+        pub fn spanning(mut self, span: Span) -> AdvancedIllError {
+            self.span = Some(span);
+            self
+        }
+
+        /// Points at `span` in `file` as well, saying `message` there.
+        pub fn label(mut self, file: &EnhancedFile, span: Span, message: &str) -> AdvancedIllError {
+            self.labels.push(Label { file: file.clone(), span, message: String::from(message) });
+            self
+        }
+
+        pub fn help(mut self, message: &str) -> AdvancedIllError {
+            self.help.push(Help { message: String::from(message), fix: None });
+            self
+        }
+
+        /// A help note that shows the line at fault with `span` replaced by `replacement`.
+        pub fn fix(mut self, message: &str, span: Span, replacement: &str) -> AdvancedIllError {
+            self.help.push(Help { message: String::from(message), fix: Some((span, String::from(replacement))) });
+            self
+        }
     }
 
     #[derive(Debug)]
@@ -122,7 +366,33 @@ pub mod ill {
         NonExistentRegister(ReadHead, String),
         NonExistentInstruction(ReadHead, String),
         ImmutableRegister(ReadHead, String),
-
+        // instruction, maximum depth
+        MaximumRecursionDepth(ReadHead, String, i32),
+        // wanted, got
+        TypeMismatch(ReadHead, String, String),
+        // index, length
+        IndexOutOfBounds(ReadHead, f64, usize),
+        NotANumber(ReadHead, String),
+        DivisionByZero(ReadHead),
+        IterationLimitExceeded(ReadHead, i32),
+        InvalidLoopStep(ReadHead, f64),
+        ModuleNotFound(ReadHead, String),
+        // two different files would both be the module of this name
+        ModuleRedefinition(ReadHead, String),
+        PrivateItem(ReadHead, String),
+        PathNotAllowed(ReadHead, String),
+        // what went wrong
+        FileError(ReadHead, String),
+        UnterminatedString(ReadHead),
+        InvalidEscape(ReadHead, char),
+        // found, expected
+        UnexpectedToken(ReadHead, String, String),
+        // `brk` or `cnt`
+        LoopControlOutsideLoop(ReadHead, String),
+        ConstantWithoutValue(ReadHead, String),
+        // the maximum call depth
+        CallDepthTooLarge(usize),
+        InvalidExitCode(ReadHead, f64),
     }
 
     impl Error for IllError {
@@ -142,6 +412,25 @@ pub mod ill {
                 NonExistentRegister(_, _) => "Register does not exist.",
                 NonExistentInstruction(_, _) => "Instruction does not exist.",
                 ImmutableRegister(_, _) => "Register cannot be mutated.",
+                MaximumRecursionDepth(_, _, _) => "Instructions were nested too deeply.",
+                TypeMismatch(_, _, _) => "A value of the wrong type was used.",
+                IndexOutOfBounds(_, _, _) => "An index was out of bounds.",
+                NotANumber(_, _) => "A String could not be converted to a Number.",
+                DivisionByZero(_) => "A Number was divided by zero.",
+                IterationLimitExceeded(_, _) => "A loop ran for too many iterations.",
+                InvalidLoopStep(_, _) => "A loop step would never reach its end.",
+                ModuleNotFound(_, _) => "A used module could not be found.",
+                ModuleRedefinition(_, _) => "Two modules have the same name.",
+                PrivateItem(_, _) => "A module item that isn't exported was used.",
+                PathNotAllowed(_, _) => "A file outside the allowed directories was opened.",
+                FileError(_, _) => "A file operation failed.",
+                UnterminatedString(_) => "A String literal was never closed.",
+                InvalidEscape(_, _) => "An unknown escape sequence was used in a String literal.",
+                UnexpectedToken(_, _, _) => "Something other than what the grammar allows was found.",
+                LoopControlOutsideLoop(_, _) => "A loop was left or continued where there's no loop.",
+                ConstantWithoutValue(_, _) => "A constant was declared without a value.",
+                CallDepthTooLarge(_) => "There's no room for as many nested calls as were allowed.",
+                InvalidExitCode(_, _) => "A program tried to exit with a code no process can have.",
             }
         }
     }
@@ -149,8 +438,11 @@ pub mod ill {
     impl IllError {
         pub fn get_actual_desc(&self) -> String {
             let x = format!("{}", self);
-            let mut spl = x.split("=> ");
-            String::from(spl.nth(1).unwrap())
+            // errors about no place in particular have no `Err@[line:column] => ` in front
+            match x.split_once("=> ") {
+                Some((_, desc)) => String::from(desc),
+                None => x.clone(),
+            }
         }
         pub fn name(&self) -> String {
             String::from(match *self {
@@ -167,7 +459,26 @@ pub mod ill {
                 UnescapedStringLiteralIsContainer(_, _) => "Unescaped String Literal Misinterpreted",
                 NonExistentRegister(_, _) => "Non-Existent Register",
                 NonExistentInstruction(_, _) => "Non-Existent Instruction",
-                ImmutableRegister(_, _) => "The Register is immutable."
+                ImmutableRegister(_, _) => "The Register is immutable.",
+                MaximumRecursionDepth(_, _, _) => "Maximum Recursion Depth Exceeded",
+                TypeMismatch(_, _, _) => "Type Mismatch",
+                IndexOutOfBounds(_, _, _) => "Index Out Of Bounds",
+                NotANumber(_, _) => "Not A Number",
+                DivisionByZero(_) => "Division By Zero",
+                IterationLimitExceeded(_, _) => "Iteration Limit Exceeded",
+                InvalidLoopStep(_, _) => "Invalid Loop Step",
+                ModuleNotFound(_, _) => "Module Not Found",
+                ModuleRedefinition(_, _) => "Module Redefinition",
+                PrivateItem(_, _) => "Private Item",
+                PathNotAllowed(_, _) => "Path Not Allowed",
+                FileError(_, _) => "File Error",
+                UnterminatedString(_) => "Unterminated String",
+                InvalidEscape(_, _) => "Invalid Escape Sequence",
+                UnexpectedToken(_, _, _) => "Unexpected Token",
+                LoopControlOutsideLoop(_, _) => "Loop Control Outside Loop",
+                ConstantWithoutValue(_, _) => "Constant Without Value",
+                CallDepthTooLarge(_) => "Call Depth Too Large",
+                InvalidExitCode(_, _) => "Invalid Exit Code",
             })
         }
     }
@@ -207,7 +518,7 @@ pub mod ill {
                     write!(
                         f,
                         "Err@{} => Found unexpected character {:?}{}",
-                        fmt_rh(&rh),
+                        fmt_rh(rh),
                         ch,
                         exp.as_ref().unwrap_or(&String::from("."))
                     )
@@ -251,7 +562,26 @@ pub mod ill {
                 UnescapedStringLiteralIsContainer(ref rh, ref got) => write!(f, "Err@{} => Found an unescaped String literal that is also a container (register / variable). Try using {:?}.", fmt_rh(rh), got),
                 NonExistentRegister(ref rh, ref name) => write!(f, "Err@{} => The container {:?} does not exist globally nor locally.", fmt_rh(rh), name),
                 NonExistentInstruction(ref rh, ref name) => write!(f, "Err@{} => The instruction {:?} does not exist.", fmt_rh(rh), name),
-                ImmutableRegister(ref rh, ref name) => write!(f, "Err@{} => The register modified here {:?} is immutable.", fmt_rh(rh), name)
+                ImmutableRegister(ref rh, ref name) => write!(f, "Err@{} => The register modified here {:?} is immutable.", fmt_rh(rh), name),
+                MaximumRecursionDepth(ref rh, ref name, depth) => write!(f, "Err@{} => Calling {:?} here would nest instructions deeper than the maximum of {}.", fmt_rh(rh), name, depth),
+                TypeMismatch(ref rh, ref wanted, ref got) => write!(f, "Err@{} => Expected a {}, but got a {} instead.", fmt_rh(rh), wanted, got),
+                IndexOutOfBounds(ref rh, index, len) => write!(f, "Err@{} => Index {} is out of bounds for a length of {}.", fmt_rh(rh), index, len),
+                NotANumber(ref rh, ref got) => write!(f, "Err@{} => {:?} is not a valid Number.", fmt_rh(rh), got),
+                DivisionByZero(ref rh) => write!(f, "Err@{} => Attempted to divide by zero.", fmt_rh(rh)),
+                IterationLimitExceeded(ref rh, max) => write!(f, "Err@{} => This loop ran for more than the maximum of {} iterations.", fmt_rh(rh), max),
+                InvalidLoopStep(ref rh, step) => write!(f, "Err@{} => A loop cannot step by {}; use a step big enough to change the counter, positive to count up or negative to count down.", fmt_rh(rh), step),
+                ModuleNotFound(ref rh, ref path) => write!(f, "Err@{} => Cannot find the module {:?} next to this file or on the module path.", fmt_rh(rh), path),
+                ModuleRedefinition(ref rh, ref name) => write!(f, "Err@{} => Another file is already used as the module {:?}.", fmt_rh(rh), name),
+                PrivateItem(ref rh, ref name) => write!(f, "Err@{} => {:?} is private to its module; mark it with '^' to export it.", fmt_rh(rh), name),
+                PathNotAllowed(ref rh, ref path) => write!(f, "Err@{} => {:?} is not inside any allowed directory.", fmt_rh(rh), path),
+                FileError(ref rh, ref msg) => write!(f, "Err@{} => {}.", fmt_rh(rh), msg),
+                UnterminatedString(ref rh) => write!(f, "Err@{} => This String literal is missing its closing '\"'.", fmt_rh(rh)),
+                InvalidEscape(ref rh, ch) => write!(f, "Err@{} => \"\\{}\" is not an escape sequence; use \\n, \\t, \\r, \\0, \\\\, \\\" or \\;.", fmt_rh(rh), ch),
+                UnexpectedToken(ref rh, ref found, ref expected) => write!(f, "Err@{} => Expected {}, but found {}.", fmt_rh(rh), expected, found),
+                LoopControlOutsideLoop(ref rh, ref name) => write!(f, "Err@{} => {:?} only works in the body of a loop, or an instruction that body calls.", fmt_rh(rh), name),
+                InvalidExitCode(ref rh, code) => write!(f, "Err@{} => Can't exit with {}; exit codes are whole numbers from 0 to 255.", fmt_rh(rh), code),
+                CallDepthTooLarge(depth) => write!(f, "A maximum call depth of {} needs more stack than can be had; lower it.", depth),
+                ConstantWithoutValue(ref rh, ref name) => write!(f, "Err@{} => The constant {:?} needs a value, since it can never be given one later.", fmt_rh(rh), name),
             }
         }
     }
@@ -260,27 +590,6 @@ pub mod ill {
         fn new() -> ReadHead {
             ReadHead { line: 1, column: 1 }
         }
-
-        fn new_by(&self, line: i32, col: i32) -> ReadHead {
-            ReadHead {
-                line: self.line + line,
-                column: self.column + col,
-            }
-        }
-
-        fn advance_by(&mut self, line: i32, col: i32) {
-            self.column += col;
-            self.line += line;
-        }
-
-        fn advance(&mut self, ch: char) {
-            if ch == NEWLINE {
-                self.advance_by(1, 0);
-                self.column = 0;
-            } else {
-                self.advance_by(0, 1);
-            }
-        }
     }
 
     #[derive(Default, Debug, Clone)]
@@ -288,18 +597,20 @@ pub mod ill {
         pub name: String,
         codes: Vec<OpCode>,
         pub scope: Vec<Register>,
-        arguments: Vec<String>,
+        pub arguments: Vec<String>,
         is_main: bool,
+        // where it was declared, so errors raised while it runs point into the right file
+        pub file: EnhancedFile,
     }
 
     impl Instruction {
         fn new_default() -> Instruction {
-            let mut scope: Vec<Register> = Vec::new();
-            scope.push(Register {
+            let scope = vec![Register {
                 identifier: "res".to_string(),
-                value: 0 as f64,
+                value: Value::default(),
                 is_variable: true,
-            });
+                ..Default::default()
+            }];
             Instruction { scope, ..Instruction::default() }
         }
 
@@ -311,141 +622,223 @@ pub mod ill {
         }
 
 
-        pub fn c_execute(&mut self, file: EnhancedFile, debug: bool, registers: &mut Vec<Register>, o_insts: Vec<Instruction>, c_scope: &mut Vec<Register>) -> Result<f64, AdvancedIllError> {
+        /// Runs every opcode of this instruction against the given context and returns its `res`, along with any
+        /// `brk` or `cnt` that cut it short (a `ret` ends here, so it comes back as `Flow::Next`).
+        pub fn c_execute(&self, ctx: &mut ExecutionContext) -> Result<(Value, Flow), AdvancedIllError> {
+            let mut flow = Flow::Next;
             for opcode in &self.codes {
-                let res = opcode.execute(file.unsafe_clone(), debug, registers, o_insts.clone(), c_scope);
-                if res.is_err() {
-                    return Err(res.err().unwrap());
+                flow = opcode.execute(ctx)?;
+                if flow != Flow::Next {
+                    break;
                 }
             }
-            let res_var = c_scope.iter().find(|x| x.identifier.to_lowercase() == String::from("res")).unwrap();
-            Ok(res_var.value)
+            let res_var = ctx.scope.iter().find(|x| x.identifier.to_lowercase() == "res").unwrap();
+            Ok((res_var.value.clone(), flow))
         }
+    }
 
-        pub fn execute(&mut self, file: EnhancedFile, debug: bool, registers: &mut Vec<Register>, o_insts: Vec<Instruction>) -> Result<(), AdvancedIllError> {
-            for opcode in &self.codes {
-                let res = opcode.execute(file.unsafe_clone().unsafe_clone(), debug, registers, o_insts.clone(), &mut self.scope);
-                if res.is_err() {
-                    return res;
-                }
-            }
-            Ok(())
+    /// How long each stage of a run took. Stages that never ran are `None`.
+    #[derive(Default, Debug, Clone, Copy)]
+    pub struct Timings {
+        pub preamble_scan: Option<Duration>,
+        pub parsing: Option<Duration>,
+        pub execution: Option<Duration>,
+    }
+
+    /// What's left over after `Interpreter::run`: the final registers, everything the program printed, and the errors
+    /// that stopped it. A program that fails to parse lists every mistake found, in order; one that fails while it
+    /// runs has just the one error that stopped it.
+    pub struct ExecutionResult {
+        pub registers: Vec<Register>,
+        // empty if the output was streamed
+        pub output: String,
+        pub errors: Vec<AdvancedIllError>,
+        pub timings: Timings,
+        // 0, the code given to `exit`, or one of `EXIT_PARSE_ERROR` and `EXIT_RUNTIME_ERROR`
+        pub exit_code: i32,
+    }
+
+    impl ExecutionResult {
+        pub fn is_ok(&self) -> bool {
+            self.errors.is_empty()
         }
     }
 
     #[derive(Default)]
     pub struct Interpreter {
         pub debug: bool,
-        pub quiet: bool,
         files: Vec<EnhancedFile>,
         preamble: Vec<EnhancedFile>,
-        opcodes: Vec<OpCode>,
         // valid opcodes
+        opcodes: OpCodeRegistry,
         pub registers: Vec<Register>,
         pub instructions: Vec<Instruction>,
+        output: Output,
+        input: Input,
+        // open files, and where they may be opened from
+        sandbox: FileTable,
+        // program arguments, exposed through `argc` and `argv`
+        args: Vec<String>,
+        // load every module of the standard library, as if each had been `use`d
+        std_library: bool,
+        // files loaded through `use`, each after the modules it uses in turn
+        modules: Vec<Program>,
+        // where `use` looks after the directory of the file using a module
+        module_path: Vec<PathBuf>,
+        // the qualified names of everything modules export
+        exported: Vec<String>,
+        // set by an `exit` that reached the main instruction
+        exit_code: Option<i32>,
+        // everything wrong with the program so far; reading goes on past most of them so one run reports them all
+        errors: Vec<AdvancedIllError>,
+        timings: Timings,
+        max_call_depth: usize,
+        max_iterations: Option<usize>,
     }
 
-    #[derive(Default)]
-    struct InstSwitchBox {
-        is_reading_definition: bool,
-        is_reading_arguments: bool,
-        is_reading_codes: bool,
-    }
+    impl ArgumentValue {
+        // a String or a Number, otherwise the name of a container
+        fn probable_literal(&self) -> Option<Either<Value, String>> {
+            match *self {
+                ArgumentValue::Number(n) => Some(Either::Left(Value::Number(n))),
+                ArgumentValue::Str(ref text) => Some(Either::Left(Value::Str(text.clone()))),
+                ArgumentValue::Name(ref name) => Some(Either::Right(name.clone())),
+                ArgumentValue::Call(_, _) => None,
+            }
+        }
 
-    fn dump_until(head: &mut ReadHead, it: &mut Peekable<Chars>, ch: Vec<char>) {
-        let _ = traverse_read(head, read_until_spare_ws(it, ch));
+        fn name(&self) -> Option<&str> {
+            match *self {
+                ArgumentValue::Name(ref name) => Some(name),
+                _ => None,
+            }
+        }
     }
 
-    fn read_until_spare_ws(it: &mut Peekable<Chars>, ch: Vec<char>) -> (i32, i32, String) {
-        let z = it.take_while(|c| !ch.contains(c)).collect::<String>();
-        let nl = newlines(&z);
-        (nl, z.len() as i32 - nl, z.chars().collect::<String>())
-    }
+    impl Interpreter {
+        fn find_opcode(&self, name: String) -> Option<&OpCode> {
+            self.opcodes.find_signature(&name)
+        }
 
-    fn newlines(x: &String) -> i32 {
-        x.chars().filter(|x| *x == NEWLINE).count() as i32
-    }
+        pub fn new(opcodes: OpCodeRegistry) -> Interpreter {
+            Interpreter {
+                opcodes,
+                max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+                ..Default::default()
+            }
+        }
 
-    fn read_until(it: &mut Peekable<Chars>, ch: Vec<char>) -> (i32, i32, String) {
-        let z = it.take_while(|c| !ch.contains(c)).collect::<String>();
-        let nl = newlines(&z);
-        (
-            nl,
-            z.len() as i32 - nl,
-            z.chars().filter(|c| !c.is_whitespace()).collect::<String>(),
-        )
-    }
+        pub fn debug(self, debug: bool) -> Interpreter {
+            Interpreter { debug, ..self }
+        }
 
-    fn any_exists_until(it: &mut Peekable<Chars>, exists: Vec<char>, until: Vec<char>) -> bool {
-        let (_, _, data) = read_until(it, until);
-        data.chars().find(|x| exists.contains(x)).is_some()
-    }
+        /// Write program output to stdout while it runs instead of capturing it, so a program that runs for a long time
+        /// doesn't hold on to everything it ever printed. `ExecutionResult::output` is empty then.
+        pub fn stream_output(mut self, stream: bool) -> Interpreter {
+            self.output.stream = stream;
+            self
+        }
 
-    fn traverse_read(head: &mut ReadHead, data: (i32, i32, String)) -> String {
-        let (row, col, dat) = data;
-        head.advance_by(row, col);
-        dat
-    }
+        /// How many instruction calls may be nested (recursion included) before `MaximumRecursionDepth` is raised. The
+        /// program runs on a thread with room for that many, so a limit too big for any stack fails with
+        /// `CallDepthTooLarge` before anything runs.
+        pub fn max_call_depth(self, max_call_depth: usize) -> Interpreter {
+            Interpreter { max_call_depth, ..self }
+        }
 
+        /// How many times a single `while` may loop before `IterationLimitExceeded` is raised. Unlimited by default.
+        pub fn max_iterations(self, max_iterations: usize) -> Interpreter {
+            Interpreter { max_iterations: Some(max_iterations), ..self }
+        }
 
-    impl Interpreter {
-        fn find_opcode(&self, name: String) -> Option<&OpCode> {
-            self.opcodes.iter().find(|x: &&OpCode| x.name == name)
+        /// Feed the program this text instead of stdin.
+        pub fn input(self, text: &str) -> Interpreter {
+            self.input_from(Cursor::new(text.as_bytes().to_vec()))
+        }
+
+        pub fn input_from<R: BufRead + Send + 'static>(self, reader: R) -> Interpreter {
+            Interpreter { input: Input::from_reader(reader), ..self }
+        }
+
+        /// Lets the program open files inside `dir` (or any directory below it). No files can be opened otherwise.
+        pub fn allow_dir<P: AsRef<Path>>(mut self, dir: P) -> Interpreter {
+            self.sandbox.allow(dir.as_ref());
+            self
+        }
+
+        /// The arguments the program sees in `argc` and `argv`.
+        pub fn args<I: IntoIterator<Item = S>, S: Into<String>>(self, args: I) -> Interpreter {
+            Interpreter { args: args.into_iter().map(Into::into).collect(), ..self }
         }
 
-        fn does_opcode_exist(&self, name: String) -> bool {
-            self.find_opcode(name).is_some()
+        /// Make the whole standard library (`math::`, `logic::`, `io::`) available without `use`.
+        pub fn std_library(self, std_library: bool) -> Interpreter {
+            Interpreter { std_library, ..self }
         }
 
-        pub fn new(debug: bool, quiet: bool, sources: Vec<NamedFile>, preamble: Vec<NamedFile>, opcodes: Vec<OpCode>) -> Interpreter {
-            if debug {
-                println!("Making Interpreter with opcodes {:?}", opcodes);
+        /// Another directory to look for `use`d modules in, after the directory of the file that uses them.
+        pub fn module_path<P: AsRef<Path>>(mut self, dir: P) -> Interpreter {
+            self.module_path.push(dir.as_ref().to_path_buf());
+            self
+        }
+
+        pub fn source(mut self, name: &str, content: &str) -> Interpreter {
+            self.files.push(EnhancedFile::new(name, content));
+            self
+        }
+
+        /// Preambles are libraries: their registers and instructions are declared, in the order the preambles were
+        /// added, before any of the main sources. They can't have a main instruction.
+        pub fn preamble(mut self, name: &str, content: &str) -> Interpreter {
+            self.preamble.push(EnhancedFile::new(name, content));
+            self
+        }
+
+        /// Runs the program on a thread of its own, with enough stack for `max_call_depth` nested calls, so running too
+        /// deep fails with `MaximumRecursionDepth` rather than overflowing the stack of whichever thread called this.
+        pub fn run(self) -> ExecutionResult {
+            let stack = self.max_call_depth.saturating_mul(STACK_PER_CALL).saturating_add(BASE_STACK);
+            let (depth, file) = (self.max_call_depth, self.files.first().cloned().unwrap_or_default());
+            let runner = match thread::Builder::new().name(String::from("pill")).stack_size(stack).spawn(move || self.run_here()) {
+                Ok(runner) => runner,
+                // nothing has run, so there's nothing else to report
+                Err(_) => return ExecutionResult {
+                    registers: Vec::new(),
+                    output: String::new(),
+                    errors: vec![AdvancedIllError::new(CallDepthTooLarge(depth), None, file)],
+                    timings: Timings::default(),
+                    exit_code: EXIT_PARSE_ERROR,
+                },
+            };
+            match runner.join() {
+                Ok(result) => result,
+                Err(panic) => panic::resume_unwind(panic),
             }
-            Interpreter {
-                opcodes,
-                debug,
-                quiet,
-                preamble: preamble
-                    .iter()
-                    .map(|nf| {
-                        let mut content = String::new();
-                        let mut clone = nf.file.try_clone().expect(&*format!(
-                            "[ERROR!]: could not create a copy of: {:?}",
-                            nf.name
-                        ));
-                        let sz = clone.read_to_string(&mut content).unwrap_or(0);
-                        if debug {
-                            println!("[:] read {} bytes for {:?}", sz, nf.file);
-                            println!("[:] content = `{:?}`", content);
-                        }
-                        EnhancedFile {
-                            filename: nf.name.clone(),
-                            file: clone,
-                            content,
-                        }
-                    })
-                    .collect(),
-                files: sources
-                    .iter()
-                    .map(|nf| {
-                        let mut content = String::new();
-                        let mut clone = nf.file.try_clone().expect(&*format!(
-                            "[ERROR!]: could not create a copy of: {:?}",
-                            nf.name
-                        ));
-                        let sz = clone.read_to_string(&mut content).unwrap_or(0);
-                        if debug {
-                            println!("[:] read {} bytes for {:?}", sz, nf.file);
-                            println!("[:] content = `{:?}`", content);
-                        }
-                        EnhancedFile {
-                            filename: nf.name.clone(),
-                            file: clone,
-                            content,
-                        }
-                    })
-                    .collect(),
-                ..Default::default()
+        }
+
+        fn run_here(mut self) -> ExecutionResult {
+            if self.debug {
+                println!("Making Interpreter with opcodes {:?}", self.opcodes);
+                for file in self.preamble.iter().chain(self.files.iter()) {
+                    println!("[:] read {} bytes for {:?}", file.content.len(), file.filename);
+                    println!("[:] content = `{:?}`", file.content);
+                }
+            }
+            self.begin_parsing();
+            let exit_code = if self.errors.is_empty() {
+                self.exit_code.unwrap_or(0)
+            } else if self.timings.execution.is_some() {
+                // only runs that got as far as executing have an execution time
+                EXIT_RUNTIME_ERROR
+            } else {
+                EXIT_PARSE_ERROR
+            };
+            ExecutionResult {
+                registers: self.registers,
+                output: self.output.buffer,
+                errors: self.errors,
+                timings: self.timings,
+                exit_code,
             }
         }
 
@@ -453,6 +846,16 @@ pub mod ill {
             self.registers.iter().find(|x: &&Register| x.identifier == name)
         }
 
+        // the register `name` means in `file`, unless a variable may take its place: a module's variables shadow every
+        // register but the module's own (and any written out in full), so its instructions work whatever names the
+        // program using it has taken
+        fn unshadowed_register(&self, file: &EnhancedFile, name: &str) -> Option<&Register> {
+            if file.module.is_some() && !name.contains(NAMESPACE_SEPARATOR) {
+                return None;
+            }
+            self.find_register(String::from(name))
+        }
+
         fn does_register_exist(&self, name: String) -> bool {
             self.find_register(name).is_some()
         }
@@ -467,123 +870,113 @@ pub mod ill {
             self.find_instruction(name).is_some()
         }
 
-        fn parse_code(&self, file: EnhancedFile, rh: ReadHead, inst: &Instruction, insts: &Vec<Instruction>, code: String) -> Result<OpCode, AdvancedIllError> {
-            fn sanitize(str: String) -> String {
-                str.replace("\"", "")
-            }
-            let mut pat = Pcre::compile(r#"('.*?'|".*?"|\S+)"#).unwrap();
-            let data = pat.matches(&*code).map(|m| m.group(0)).collect::<Vec<_>>();
-            let code_name = data[0].to_string();
-            let nls = newlines(&code) as usize;
-            let error_rh = rh.new_by(-(nls as i32), ((-rh.column) + code.len() as i32));
-            //println!("Looking for: {:?}, code = {:?}, data[0] = {:?}, data = {:?}", code_name.clone(), code.clone(), data[0].to_string(), data);
-            if !self.does_opcode_exist(code_name.clone()) {
-                let err = UnknownOpCode(
-                    error_rh,
-                    code_name.clone(),
-                );
-                let adv_err = AdvancedIllError::new(err, Some(error_rh), file);
-                return Err(adv_err);
-            }
-            let opcode = self.find_opcode(code_name.clone()).unwrap().clone();
-            if (data.len() - 1) != opcode.arguments.len() {
+        // matches a code against the signature of its OpCode, which decides what each argument is: a literal, a
+        // container or an instruction
+        fn resolve_code(&self, file: &EnhancedFile, inst: &Instruction, code: &Code) -> Result<OpCode, AdvancedIllError> {
+            let error = |err: IllError, span: Span| AdvancedIllError::new(err, Some(span.head()), file.clone()).spanning(span);
+            let head = code.span.head();
+            let code_name = code.name.clone();
+            let opcode = match self.find_opcode(code_name.clone()) {
+                Some(opcode) => opcode.clone(),
+                None => return Err(error(UnknownOpCode(head, code_name), code.span)),
+            };
+            let data = &code.arguments;
+            let given = data.len();
+            if given < opcode.required_arguments() || given > opcode.arguments.len() {
+                let expected = if given < opcode.required_arguments() { opcode.required_arguments() } else { opcode.arguments.len() };
                 let err = OpCodeArgumentMismatch(
-                    error_rh,
-                    sanitize(data[0].to_string()),
-                    opcode.arguments.len() as i32,
-                    (data.len() - 1) as i32,
+                    head,
+                    code_name,
+                    expected as i32,
+                    given as i32,
                 );
-                let adv_err = AdvancedIllError::new(err, Some(error_rh), file.unsafe_clone());
-                return Err(adv_err);
-            }
-
-            fn is_arg_literal(arg: String) -> bool {
-                arg.parse::<f64>().is_ok()
-            }
-
-            fn is_arg_string(arg: String) -> bool {
-                if arg.starts_with("\"") && arg.ends_with("\"") {
-                    true
-                } else {
-                    arg.chars().find(|x| x.is_numeric()).is_none() // just make sure its [A-z]
-                }
+                return Err(error(err, code.span.to(&data.last().map_or(code.span, |x| x.span))));
             }
 
-            fn is_container(instruc: &Instruction, int: &Interpreter, ctx: String) -> bool {
-                int.does_register_exist(ctx.clone()) || instruc.does_scoped_register_exist(ctx)
-            }
-
-            fn strip_quotes(str: String) -> String {
-                str.replace("\"", "")
-            }
+            let is_container = |instruc: &Instruction, ctx: String| {
+                self.unshadowed_register(file, &ctx).is_some() || instruc.arguments.contains(&ctx) || instruc.does_scoped_register_exist(ctx)
+            };
 
             let exp_args = opcode.arguments.clone();
             let mut act_args: Vec<ExpressionType> = Vec::new();
-            for i in 0..exp_args.len() {
-                let expected = exp_args[i].clone().into();
-                let ref argument = data[i + 1].to_string();
+            for (i, argument) in data.iter().enumerate() {
+                let expected: ExpressionType = exp_args[i].clone();
+                let arg_head = argument.span.head();
+                let text = argument.span.text(file);
                 if self.debug {
-                    println!("arg = {}, expected = {:?}", argument, expected);
+                    println!("arg = {}, expected = {:?}", text, expected);
                 }
+                let invalid = |e_type: ExpressionType| error(OpCodeInvalidArgument(arg_head, e_type, String::from(text)), argument.span);
                 match expected {
                     ExpressionType::ProbableLiteral(_) => {
-                        if is_arg_literal(argument.clone()) {
-                            let mut a_clone = argument.clone();
-                            if !a_clone.contains(".") {
-                                a_clone.push('.');
-                            }
-                            act_args.push(ExpressionType::ProbableLiteral(Either::Left(a_clone.parse::<f64>().unwrap())));
-                        } else {
-                            act_args.push(ExpressionType::ProbableLiteral(Either::Right(argument.clone())));
-                        }
+                        let literal = argument.value.probable_literal().ok_or_else(|| invalid(expected.clone()))?;
+                        act_args.push(ExpressionType::ProbableLiteral(self.qualify_literal(file, inst, arg_head, literal)?));
                     }
-                    ExpressionType::IntegerLiteral(_) => {
-                        let mut a_clone = argument.clone();
-                        if !a_clone.contains(".") {
-                            a_clone.push('.');
-                        }
-                        act_args.push(ExpressionType::IntegerLiteral(a_clone.parse::<f64>().unwrap()));
-                    }
-
-                    ExpressionType::StringLiteral(_) => {
-                        if is_container(inst, self, argument.clone()) {
-                            let err = UnescapedStringLiteralIsContainer(
-                                error_rh,
-                                argument.clone(),
-                            );
-                            let adv_err = AdvancedIllError::new(err, Some(error_rh), file);
-                            return Err(adv_err);
-                        } else if !is_arg_string(argument.clone()) {
-                            let err = OpCodeInvalidArgument(
-                                error_rh,
-                                s_literal(),
-                                argument.clone(),
-                            );
-                            let adv_err = AdvancedIllError::new(err, Some(error_rh), file);
-                            return Err(adv_err);
-                        } else {
-                            act_args.push(ExpressionType::StringLiteral(strip_quotes(argument.clone())));
+                    ExpressionType::IntegerLiteral(_) => match argument.value {
+                        ArgumentValue::Number(n) => act_args.push(ExpressionType::IntegerLiteral(n)),
+                        _ => return Err(invalid(expected.clone())),
+                    },
+
+                    ExpressionType::StringLiteral(_) => match (&argument.value, argument.value.name()) {
+                        (ArgumentValue::Str(text), _) => act_args.push(ExpressionType::StringLiteral(text.clone())),
+                        (_, Some(name)) => {
+                            if is_container(inst, self.qualify(file, inst, arg_head, name)?) {
+                                let err = error(UnescapedStringLiteralIsContainer(arg_head, String::from(name)), argument.span);
+                                return Err(err.fix("quote it to use the text itself", argument.span, &format!("{:?}", name)));
+                            } else if name.chars().any(|x| x.is_numeric()) {
+                                return Err(invalid(s_literal()));
+                            }
+                            act_args.push(ExpressionType::StringLiteral(String::from(name)));
                         }
-                    }
+                        _ => return Err(invalid(s_literal())),
+                    },
 
                     ExpressionType::ContainerReference(_) => {
-                        act_args.push(ExpressionType::ContainerReference(argument.clone()));
+                        let name = self.qualify(file, inst, arg_head, argument.value.name().ok_or_else(|| invalid(expected.clone()))?)?;
+                        // parameters shadow registers, so only a register that isn't shadowed is known to be constant here
+                        let is_constant = !inst.arguments.contains(&name) && self.unshadowed_register(file, &name).is_some_and(|x| x.is_constant);
+                        if opcode.mutates(i) && is_constant {
+                            return Err(error(ImmutableRegister(arg_head, name), argument.span));
+                        }
+                        act_args.push(ExpressionType::ContainerReference(name));
                     }
                     ExpressionType::RegisterReference(_) => {
-                        act_args.push(ExpressionType::RegisterReference(argument.clone()));
+                        let name = argument.value.name().ok_or_else(|| invalid(expected.clone()))?;
+                        act_args.push(ExpressionType::RegisterReference(self.qualify(file, inst, arg_head, name)?));
                     }
 
                     ExpressionType::VariableReference(_) => {
-                        act_args.push(ExpressionType::VariableReference(argument.clone()));
+                        let name = argument.value.name().ok_or_else(|| invalid(expected.clone()))?;
+                        // a constant register is never a variable, but naming one is a mistake of its own
+                        let qualified = self.qualify(file, inst, arg_head, name)?;
+                        let is_constant = !inst.arguments.contains(&qualified) && self.unshadowed_register(file, &qualified).is_some_and(|x| x.is_constant);
+                        if opcode.mutates(i) && is_constant {
+                            return Err(error(ImmutableRegister(arg_head, qualified), argument.span));
+                        }
+                        act_args.push(ExpressionType::VariableReference(String::from(name)));
                     }
                     ExpressionType::InstructionReference(_, _) => {
-                        let z = insts.iter().find(|x| x.name == argument.clone());
-                        if z.is_some() {
-                            act_args.push(ExpressionType::InstructionReference(argument.clone(), z.unwrap().arguments.clone()));
-                        } else {
-                            let err = NonExistentInstruction(error_rh, argument.clone());
-                            let adv_err = AdvancedIllError::new(err, Some(error_rh), file);
-                            return Err(adv_err);
+                        let (name, args) = match argument.value {
+                            ArgumentValue::Name(ref name) => (self.qualify(file, inst, arg_head, name)?, &[][..]),
+                            ArgumentValue::Call(ref name, ref args) => (self.qualify(file, inst, arg_head, name)?, &args[..]),
+                            _ => return Err(invalid(expected.clone())),
+                        };
+                        let mut call_args = Vec::new();
+                        for arg in args {
+                            let arg_head = arg.span.head();
+                            match arg.value.probable_literal() {
+                                Some(literal) => call_args.push(self.qualify_literal(file, inst, arg_head, literal)?),
+                                None => return Err(error(OpCodeInvalidArgument(arg_head, ExpressionType::ProbableLiteral(Either::Right(String::new())), String::from(arg.span.text(file))), arg.span)),
+                            }
+                        }
+                        // every instruction is declared before any code is resolved, so this may be one further down
+                        let z = self.find_instruction(name.clone());
+                        match z {
+                            Some(z) if z.arguments.len() != call_args.len() => {
+                                return Err(error(OpCodeArgumentMismatch(arg_head, name, z.arguments.len() as i32, call_args.len() as i32), argument.span));
+                            }
+                            Some(_) => act_args.push(ExpressionType::InstructionReference(name, call_args)),
+                            None => return Err(error(NonExistentInstruction(arg_head, name), argument.span)),
                         }
                     }
                 }
@@ -591,216 +984,322 @@ pub mod ill {
             Ok(OpCode {
                 name: code_name,
                 arguments: act_args,
-                location: Some(error_rh),
+                location: Some(head),
+                ..Default::default()
             })
         }
 
-        fn scan_instructions(&mut self, preamble: bool) -> (Result<(), AdvancedIllError>, Option<Duration>) {
-            fn read_inst_def(it: &mut Peekable<Chars>) -> (i32, i32, String) {
-                read_until(it, vec![INST_PARAM_BEGIN])
-            }
-
-            let mut master_file: Option<EnhancedFile> = None;
-            for e_file in if preamble { &self.preamble } else { &self.files } {
-                let file = e_file.try_clone().unwrap();
-                let mut it = e_file.content.chars().peekable();
-                let mut head: ReadHead = ReadHead::new();
-                let mut cur_inst: Instruction = Instruction::new_default();
-                let mut cur_inst_sb: InstSwitchBox = Default::default();
-                while let Some(x) = it.next() {
-                    head.advance(x);
-                    if x == COMMENT_SINGLE_LINE {
-                        dump_until(&mut head, it.by_ref(), vec![NEWLINE]);
-                    } else if x == INST_DEF {
-                        if cur_inst_sb.is_reading_definition {
-                            let err = UnexpectedCharacter(
-                                head,
-                                x,
-                                Some(String::from(", expecting instruction identifier.")),
-                            );
-                            let adv_err = AdvancedIllError::new(err, Some(head), file);
-                            return (Err(adv_err), None);
-                        } else {
-                            cur_inst_sb.is_reading_definition = true;
+        // adds every instruction `programs` declare, codes still unresolved, so codes can call instructions that are
+        // declared after them or in another file. Even a broken declaration is added, so `scan_instructions` finds each
+        // instruction at the index it expects.
+        fn declare_instructions(&mut self, programs: &[Program], library: bool) {
+            for program in programs {
+                let file = &program.file;
+                for decl in &program.instructions {
+                    let head = decl.span.head();
+                    if decl.is_main && library {
+                        let err = UnexpectedCharacter(head, INST_DEF, Some(String::from(", modules and preambles can't have a main instruction.")));
+                        self.errors.push(AdvancedIllError::new(err, Some(head), file.clone()));
+                    }
+                    let name = match file.module {
+                        Some(ref namespace) => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, decl.name),
+                        None => decl.name.clone(),
+                    };
+                    if self.does_instruction_exist(name.clone()) {
+                        self.errors.push(AdvancedIllError::new(InstructionRedefinition(head, name.clone()), Some(head), file.clone()));
+                    }
+                    if decl.is_exported && file.module.is_some() {
+                        self.exported.push(name.clone());
+                    }
+                    self.instructions.push(Instruction {
+                        name,
+                        arguments: decl.parameters.clone(),
+                        is_main: decl.is_main && !library,
+                        file: file.clone(),
+                        ..Instruction::new_default()
+                    });
+                }
+            }
+        }
+
+        // resolves the codes of the instructions `programs` declare, which were declared starting at `first`, then runs
+        // the main instruction if nothing went wrong; the execution time comes back if it ran
+        fn scan_instructions(&mut self, programs: &[Program], first: usize, library: bool) -> Option<Duration> {
+            let mut index = first;
+            for program in programs {
+                for decl in &program.instructions {
+                    let mut codes = Vec::new();
+                    for code in &decl.codes {
+                        match self.resolve_code(&program.file, &self.instructions[index], code) {
+                            Ok(opcode) => codes.push(opcode),
+                            Err(err) => self.errors.push(err),
                         }
-                        if cur_inst_sb.is_reading_definition {
-                            cur_inst.is_main = *it.peek().unwrap() == INST_DEF;
-                            if cur_inst.is_main {
-                                master_file = Some(file.unsafe_clone());
-                            }
-                            let register_name = traverse_read(&mut head, read_inst_def(it.by_ref()));
-                            cur_inst.name = register_name;
-                            cur_inst_sb.is_reading_arguments = true;
-                            let params_unsp =
-                                traverse_read(
-                                    &mut head,
-                                    read_until_spare_ws(it.by_ref(), vec![INST_PARAM_END]),
-                                );
-                            let params: Vec<_> = params_unsp
-                                .split(" ")
-                                .map(|x: &str| String::from(x))
-                                .collect();
-                            cur_inst.arguments = params;
-                            cur_inst_sb.is_reading_arguments = false;
-                            if !any_exists_until(
-                                &mut it.clone(),
-                                vec![INST_CODES_BEGIN],
-                                vec![INST_CODES_END],
-                            )
-                                {
-                                    let err = UnexpectedCharacter(
-                                        head,
-                                        *it.peek().unwrap(),
-                                        Some(format!(
-                                            ", expecting instruction code beginning \"{}\".",
-                                            INST_CODES_BEGIN
-                                        )));
-                                    let adv_err = AdvancedIllError::new(err, Some(head), file);
-                                    return (Err(adv_err), None);
-                                }
-                            dump_until(&mut head, it.by_ref(), vec![INST_CODES_BEGIN]);
-                            while it.peek().is_some() && *it.peek().unwrap() != INST_CODES_END {
-                                if !any_exists_until(
-                                    &mut it.clone(),
-                                    vec![DEF_END],
-                                    vec![INST_CODES_END],
-                                )
-                                    {
-                                        // break because no codes
-                                        break;
-                                    }
-
-                                let raw_code = traverse_read(
-                                    &mut head,
-                                    read_until_spare_ws(it.by_ref(), vec![DEF_END]),
-                                );
-
-                                let code = String::from(raw_code.trim());
-                                let res = self.parse_code(file.unsafe_clone(), head.clone(), &cur_inst, &self.instructions, code.clone());
-                                if res.is_err() {
-                                    return (Err(res.err().unwrap()), None);
-                                }
-                                cur_inst.codes.push(res.ok().unwrap());
-                                if self.debug {
-                                    println!("found code {:?}", code);
-                                }
-                            }
-                            cur_inst_sb.is_reading_codes = false;
-                            if self.does_instruction_exist(cur_inst.name.clone()) {
-                                let head = head.new_by(0, -(cur_inst.name.len() as i32));
-                                let err = IllError::InstructionRedefinition(
-                                    head.new_by(0, -(cur_inst.name.len() as i32)),
-                                    cur_inst.name,
-                                );
-                                let adv_err = AdvancedIllError::new(err, Some(head), file);
-                                return (Err(adv_err), None);
-                            }
-                            self.instructions.push(cur_inst);
-                            cur_inst = Instruction::new_default();
-                            cur_inst_sb = Default::default();
+                        if self.debug {
+                            println!("found code {:?}", code.span.to(&code.arguments.last().map_or(code.span, |x| x.span)).text(&program.file));
                         }
                     }
+                    self.instructions[index].codes = codes;
+                    index += 1;
                 }
             }
-            if self.instructions.len() == 0 {
-                return (Err(AdvancedIllError::new(NoMainInstruction(), None, self.files[0].unsafe_clone())), None);
-            } else if self.instructions.len() == 1 {
-                self.instructions[0].is_main = true;
-                master_file = Some(self.files[0].unsafe_clone());
+            if library || !self.errors.is_empty() {
+                return None;
+            }
+            // a lone instruction is main, as long as it doesn't come from a library
+            if index - first == 1 {
+                self.instructions[first].is_main = true;
+            }
+            if !self.instructions.iter().any(|x| x.is_main) {
+                let file = self.files.first().cloned().unwrap_or_default();
+                self.errors.push(AdvancedIllError::new(NoMainInstruction(), None, file));
+                return None;
             }
             if self.debug {
                 println!("insts = {:?}", self.instructions);
             }
-            if !preamble {
-                // inst.execute(debug, &self.registers, &self.instructions);
-                let mut res = Ok(());
-                let dur = Duration::span(|| {
-                    let inst_clone = self.instructions.clone();
-                    let debug = self.debug;
-                    let mut_inst: &mut Vec<Instruction> = self.instructions.as_mut();
-                    let inst = mut_inst.iter_mut().find(|x| x.is_main).unwrap();
-                    res = inst.execute(master_file.unwrap(), debug, &mut self.registers, inst_clone)
-                });
-                if !self.quiet {
-                    println!("Pill Main Instruction Execution took {}s ({}ms).", dur.num_seconds(), dur.num_milliseconds());
+            // inst.execute(debug, &self.registers, &self.instructions);
+            let mut res = Ok(Flow::Next);
+            let dur = Duration::span(|| {
+                let main = self.instructions.iter().find(|x| x.is_main).unwrap();
+                let mut scope = main.scope.clone();
+                res = {
+                    let mut ctx = ExecutionContext {
+                        file: &main.file,
+                        debug: self.debug,
+                        registers: &mut self.registers,
+                        scope: &mut scope,
+                        output: &mut self.output,
+                        input: &mut self.input,
+                        files: &mut self.sandbox,
+                        instructions: &self.instructions,
+                        opcodes: &self.opcodes,
+                        depth: 0,
+                        max_depth: self.max_call_depth,
+                        max_iterations: self.max_iterations,
+                        in_loop: false,
+                    };
+                    main.c_execute(&mut ctx).map(|(_, flow)| flow)
+                };
+            });
+            match res {
+                Err(err) => self.errors.push(err),
+                Ok(Flow::Exit(code)) => self.exit_code = Some(code),
+                Ok(_) => {}
+            }
+            Some(dur)
+        }
+
+        fn load_modules(&mut self, roots: &[Program]) {
+            let mut seen = Vec::new();
+            if self.std_library {
+                let importer = self.files.first().cloned().unwrap_or_default();
+                for &(name, _) in stdlib::MODULES {
+                    if let Err(err) = self.load_module(&importer, ReadHead::new(), &format!("{}{}", STD_PREFIX, name), &mut seen) {
+                        self.errors.push(err);
+                    }
                 }
-                if res.is_err() {
-                    return (Err(res.err().unwrap()), Some(dur));
+            }
+            for program in roots {
+                self.load_uses(program, &mut seen);
+            }
+        }
+
+        // loads the modules `program` uses, and the ones they use, dependencies first
+        fn load_uses(&mut self, program: &Program, seen: &mut Vec<PathBuf>) {
+            for used in &program.uses {
+                if let Err(err) = self.load_module(&program.file, used.span.head(), &used.path, seen) {
+                    self.errors.push(err);
                 }
-                (res, Some(dur))
+            }
+        }
+
+        // loads the module `importer` refers to as `path` (a file, or `std::name`) unless it already has been
+        fn load_module(&mut self, importer: &EnhancedFile, head: ReadHead, path: &str, seen: &mut Vec<PathBuf>) -> Result<(), AdvancedIllError> {
+            let not_found = || AdvancedIllError::new(ModuleNotFound(head, String::from(path)), Some(head), importer.clone());
+            let (key, module) = if let Some(name) = path.strip_prefix(STD_PREFIX) {
+                let content = stdlib::find(name).ok_or_else(not_found)?;
+                (PathBuf::from(path), EnhancedFile::module(&format!("<std>/{}.ill", name), content, name))
             } else {
-                (Ok(()), None)
-            }
-        }
-
-        fn create_registers(&mut self) -> Result<(), AdvancedIllError> {
-            for e_file in &self.files {
-                let mut iter = e_file.content.chars().peekable();
-                let mut head: ReadHead = ReadHead::new();
-                let mut has_found_registers: bool = false;
-                let file = e_file.try_clone().unwrap();
-                while let Some(x) = iter.next() {
-                    head.advance(x);
-                    if !x.is_whitespace() {
-                        if x == REGISTER_DEF {
-                            has_found_registers = true;
-                            while iter.peek().is_some() && *iter.peek().unwrap() != NEWLINE {
-                                let register_name = traverse_read(
-                                    &mut head,
-                                    read_until(iter.by_ref(), vec![DEF_END]),
-                                );
-                                if self.does_register_exist(register_name.clone()) {
-                                    let err_str = register_name.clone();
-                                    let adv_err: AdvancedIllError = AdvancedIllError::new(RegisterRedefinition(head, err_str, None), Some(head), file);
-                                    return Err(adv_err);
-                                }
-                                self.registers.push(Register {
-                                    identifier: register_name,
-                                    is_variable: false,
-                                    ..Default::default()
-                                });
-                                continue;
-                            }
-                        }
+                let importer_dir = Path::new(&importer.filename).parent().map(Path::to_path_buf).unwrap_or_default();
+                let resolved = match Some(&importer_dir).into_iter().chain(self.module_path.iter()).map(|x| x.join(path)).find(|x| x.is_file()) {
+                    Some(resolved) => resolved,
+                    None => return Err(not_found()),
+                };
+                let canonical = resolved.canonicalize().unwrap_or_else(|_| resolved.clone());
+                if seen.contains(&canonical) {
+                    return Ok(());
+                }
+                let content = fs::read_to_string(&resolved).map_err(|_| not_found())?;
+                let namespace = resolved.file_stem().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default();
+                (canonical, EnhancedFile::module(&resolved.to_string_lossy(), &content, &namespace))
+            };
+            if seen.contains(&key) {
+                return Ok(());
+            }
+            seen.push(key);
+            let (module, errors) = parse(&module);
+            self.errors.extend(errors);
+            self.load_uses(&module, seen);
+            if self.modules.iter().any(|x| x.file.module == module.file.module) {
+                return Err(AdvancedIllError::new(ModuleRedefinition(head, module.file.module.clone().unwrap_or_default()), Some(head), importer.clone()));
+            }
+            self.modules.push(module);
+            Ok(())
+        }
+
+        // resolves a name used in `file`: inside a module its own registers and instructions come first, and
+        // `module::name` must be exported unless it's used from that very module
+        fn qualify(&self, file: &EnhancedFile, inst: &Instruction, head: ReadHead, name: &str) -> Result<String, AdvancedIllError> {
+            let declared = |name: &str| self.does_register_exist(String::from(name)) || self.does_instruction_exist(String::from(name));
+            if let Some(pos) = name.find(NAMESPACE_SEPARATOR) {
+                let is_own = file.module.as_ref().is_some_and(|x| *x == name[..pos]);
+                if !is_own && declared(name) && !self.exported.iter().any(|x| x == name) {
+                    return Err(AdvancedIllError::new(PrivateItem(head, String::from(name)), Some(head), file.clone()));
+                }
+                return Ok(String::from(name));
+            }
+            if let Some(ref namespace) = file.module {
+                let qualified = format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name);
+                // parameters shadow the module's own names
+                if !inst.arguments.iter().any(|x| x == name) && declared(&qualified) {
+                    return Ok(qualified);
+                }
+            }
+            Ok(String::from(name))
+        }
+
+        fn qualify_literal(&self, file: &EnhancedFile, inst: &Instruction, head: ReadHead, literal: Either<Value, String>) -> Result<Either<Value, String>, AdvancedIllError> {
+            match literal {
+                Either::Right(name) => self.qualify(file, inst, head, &name).map(Either::Right),
+                value => Ok(value),
+            }
+        }
+
+        // declares the registers of every program in turn; only `roots` (the main sources) must have any. A register whose
+        // value can't be worked out is still declared, so the codes using it don't fail as well.
+        fn create_registers(&mut self, programs: &[&Program], roots: usize) {
+            let first_root = programs.len() - roots;
+            // where each register was declared, to point redefinitions back at it
+            let mut declared: Vec<(String, &EnhancedFile, Span)> = Vec::new();
+            for (index, program) in programs.iter().enumerate() {
+                let file = &program.file;
+                for decl in &program.registers {
+                    let head = decl.span.head();
+                    let register_name = match file.module {
+                        Some(ref namespace) => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, decl.name),
+                        None => decl.name.clone(),
+                    };
+                    if decl.is_exported && file.module.is_some() {
+                        self.exported.push(register_name.clone());
+                    }
+                    if self.does_register_exist(register_name.clone()) {
+                        let err = AdvancedIllError::new(RegisterRedefinition(head, register_name.clone(), None), Some(head), file.clone()).spanning(decl.span);
+                        self.errors.push(match declared.iter().find(|x| x.0 == register_name) {
+                            Some(&(_, original, span)) => err.label(original, span, "first declared here"),
+                            None => err.help(&format!("{:?} is declared by pill itself; pick another name.", register_name)),
+                        });
+                        continue;
                     }
+                    let value = match decl.initializer {
+                        // may use any register defined before it
+                        Some(ref initial) => evaluate(file, initial, &self.registers, &self.exported).unwrap_or_else(|err| {
+                            self.errors.push(err);
+                            Value::default()
+                        }),
+                        None if decl.is_constant => {
+                            let err = AdvancedIllError::new(ConstantWithoutValue(head, register_name.clone()), Some(head), file.clone()).spanning(decl.span);
+                            self.errors.push(err.help(&format!("give it one where it's declared, like `+!{}=0;`", decl.name)));
+                            Value::default()
+                        }
+                        None => Value::default(),
+                    };
+                    declared.push((register_name.clone(), file, decl.span));
+                    self.registers.push(Register {
+                        identifier: register_name,
+                        value,
+                        is_variable: false,
+                        is_constant: decl.is_constant,
+                    });
                 }
-                if !has_found_registers {
-                    let adv_err: AdvancedIllError = AdvancedIllError::new(NoRegistersFound(e_file.unsafe_clone()), Some(head), file);
-                    return Err(adv_err);
+                // preambles and modules don't need registers of their own
+                if program.registers.is_empty() && index >= first_root {
+                    self.errors.push(AdvancedIllError::new(NoRegistersFound(file.clone()), None, file.clone()));
                 } else if self.debug {
                     println!("Found registers: {:?}", self.registers);
                 }
             }
-            Ok(())
         }
 
-        pub fn begin_parsing(&mut self) -> Option<AdvancedIllError> {
-            let inst_scan = Duration::span(|| {
-                self.scan_instructions(true);
+        // registers every program starts out with
+        fn predefine_registers(&mut self) {
+            self.registers.push(Register {
+                identifier: String::from(EOF_REGISTER),
+                value: Value::Number(FALSE),
+                is_variable: false,
+                ..Default::default()
+            });
+            let argv: List<Value> = self.args.iter().cloned().map(Value::Str).collect::<Vec<_>>().into();
+            self.registers.push(Register {
+                identifier: String::from(ARGC_REGISTER),
+                value: Value::Number(argv.len() as f64),
+                is_variable: false,
+                is_constant: true,
             });
+            self.registers.push(Register {
+                identifier: String::from(ARGV_REGISTER),
+                value: Value::List(argv),
+                is_variable: false,
+                is_constant: true,
+            });
+        }
 
-            let res: Result<(), AdvancedIllError> = self.create_registers();
-            if res.is_err() {
-                return res.err();
+        // reads everything, then runs the main instruction as long as nothing went wrong. Whatever parsed cleanly is
+        // still checked after a syntax error, so one run reports every mistake it can.
+        fn begin_parsing(&mut self) {
+            self.predefine_registers();
+
+            let parse_all = |files: &[EnhancedFile], errors: &mut Vec<AdvancedIllError>| -> Vec<Program> {
+                files.iter().map(|file| {
+                    let (program, found) = parse(file);
+                    errors.extend(found);
+                    program
+                }).collect()
+            };
+            let preamble = parse_all(&self.preamble, &mut self.errors);
+            let sources = parse_all(&self.files, &mut self.errors);
+
+            let roots: Vec<Program> = preamble.iter().chain(sources.iter()).cloned().collect();
+            self.load_modules(&roots);
+            let broken = !self.errors.is_empty();
+
+            let modules = self.modules.clone();
+            // in the order their instructions are read, so an initializer can use the registers of any module
+            let programs: Vec<&Program> = modules.iter().chain(preamble.iter()).chain(sources.iter()).collect();
+            self.create_registers(&programs, sources.len());
+
+            // libraries (modules, dependencies first, then the preamble) are read before the sources that use them
+            let library: Vec<Program> = modules.into_iter().chain(preamble).collect();
+            self.declare_instructions(&library, true);
+            let first_source = self.instructions.len();
+            self.declare_instructions(&sources, false);
+
+            let inst_scan = Duration::span(|| { self.scan_instructions(&library, 0, true); });
+            self.timings.preamble_scan = Some(inst_scan);
+
+            let mut time = None;
+            let sscan_dur = Duration::span(|| time = self.scan_instructions(&sources, first_source, false));
+            if let Some(execution) = time {
+                self.timings.parsing = Some(sscan_dur.sub(execution));
+                self.timings.execution = time;
             }
 
-            let mut res = (Ok(()), None);
-            let sscan_dur = Duration::span(|| res = self.scan_instructions(false));
-            let (a_res, time) = res; // destructure this tuple like im about to destructure misconceptions about race mixing
-            if time.is_some() {
-                let r_t = sscan_dur.sub(time.unwrap());
-                if !self.quiet {
-                    println!("Pill Preamble Instruction Scan took {}s, ({}ms).", inst_scan.num_seconds(), inst_scan.num_milliseconds());
-                    println!("Pill Main Instruction Parsing took ({}s, ({}ms).", r_t.num_seconds(), r_t.num_milliseconds());
-                }
-            } else {
-                if !self.quiet {
-                    println!("Encountered an error therefore any calculations of time are voided.");
-                }
+            if broken {
+                // a name the broken files (or missing modules) would have declared is unknown only because of that
+                self.errors.retain(|x| !matches!(*x.error, NonExistentRegister(_, _) | NonExistentInstruction(_, _) | NoRegistersFound(_)));
+                return;
             }
-
-            if a_res.is_err() {
-                return a_res.err();
+            if !self.errors.is_empty() {
+                return;
             }
 
             if self.debug {
@@ -809,8 +1308,6 @@ pub mod ill {
                     println!("{}'s registers: {:?}", inst.name, inst.scope);
                 }
             }
-
-            None
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,355 @@
+pub mod ill {
+    use std::fmt;
+    use std::fmt::{Display, Formatter};
+
+    use interpreter::ill::{AdvancedIllError, EnhancedFile, IllError, ReadHead};
+
+    const NEWLINE: char = '\n';
+    const COMMENT_SINGLE_LINE: char = '>';
+    const QUOTE: char = '"';
+    const ESCAPE: char = '\\';
+    // each of these is a token on its own
+    const SYMBOLS: &str = "+$!^=;(){}-*/%";
+
+    /// Where a token sits in its file: byte offsets into the source, and the line and column (both counted from 1,
+    /// columns in characters) of its first character.
+    #[derive(Default, Debug, Clone, Copy, PartialEq)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+        pub line: i32,
+        pub column: i32,
+    }
+
+    impl Span {
+        pub fn head(&self) -> ReadHead {
+            ReadHead { line: self.line, column: self.column }
+        }
+
+        /// Whether `next` starts exactly where this ends, like the `(` of `inst(x)` or the digits of `-3`.
+        pub fn touches(&self, next: &Span) -> bool {
+            self.end == next.start
+        }
+
+        /// From the start of this span to the end of `other`.
+        pub fn to(&self, other: &Span) -> Span {
+            Span { end: other.end, ..*self }
+        }
+
+        /// The source text this span covers.
+        pub fn text<'a>(&self, file: &'a EnhancedFile) -> &'a str {
+            &file.content()[self.start..self.end]
+        }
+
+        /// The line and column just past the last character; a String may run over several lines.
+        pub fn end_head(&self, file: &EnhancedFile) -> ReadHead {
+            let text = self.text(file);
+            match text.rfind(NEWLINE) {
+                Some(pos) => ReadHead { line: self.line + text.matches(NEWLINE).count() as i32, column: text[pos + 1..].chars().count() as i32 + 1 },
+                None => ReadHead { line: self.line, column: self.column + text.chars().count() as i32 },
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum TokenKind {
+        // names, OpCodes and keywords, including qualified ones like `math::fact`
+        Word(String),
+        Number(f64),
+        // a quoted String, with its escape sequences already replaced
+        Str(String),
+        Symbol(char),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Token {
+        pub kind: TokenKind,
+        pub span: Span,
+    }
+
+    impl Token {
+        pub fn is_symbol(&self, ch: char) -> bool {
+            self.kind == TokenKind::Symbol(ch)
+        }
+
+        pub fn word(&self) -> Option<&str> {
+            match self.kind {
+                TokenKind::Word(ref word) => Some(word),
+                _ => None,
+            }
+        }
+    }
+
+    impl Display for Token {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            match self.kind {
+                TokenKind::Word(ref word) => write!(f, "`{}`", word),
+                TokenKind::Number(n) => write!(f, "`{}`", n),
+                TokenKind::Str(ref s) => write!(f, "{:?}", s),
+                TokenKind::Symbol(ch) => write!(f, "`{}`", ch),
+            }
+        }
+    }
+
+    fn is_word_char(ch: char) -> bool {
+        ch.is_alphanumeric() || ch == '_' || ch == ':' || ch == '.'
+    }
+
+    struct Lexer<'a> {
+        file: &'a EnhancedFile,
+        // a byte offset
+        pos: usize,
+        line: i32,
+        column: i32,
+        // lexing carries on past a bad character or escape, so one run can report all of them
+        errors: Vec<AdvancedIllError>,
+    }
+
+    impl<'a> Lexer<'a> {
+        fn peek(&self) -> Option<char> {
+            self.file.content()[self.pos..].chars().next()
+        }
+
+        fn offset(&self) -> usize {
+            self.pos
+        }
+
+        fn bump(&mut self) -> Option<char> {
+            let ch = self.peek()?;
+            self.pos += ch.len_utf8();
+            if ch == NEWLINE {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            Some(ch)
+        }
+
+        fn error(&mut self, err: IllError, head: ReadHead) {
+            self.errors.push(AdvancedIllError::new(err, Some(head), self.file.clone()));
+        }
+
+        // the rest of a String literal; one that never ends takes the rest of the file
+        fn string(&mut self, head: ReadHead) -> TokenKind {
+            let mut text = String::new();
+            loop {
+                let escape_head = ReadHead { line: self.line, column: self.column };
+                match self.bump() {
+                    Some(QUOTE) => return TokenKind::Str(text),
+                    Some(ESCAPE) => match self.bump() {
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        Some('r') => text.push('\r'),
+                        Some('0') => text.push('\0'),
+                        Some(ch @ '\\') | Some(ch @ '"') | Some(ch @ ';') => text.push(ch),
+                        Some(ch) => {
+                            self.error(IllError::InvalidEscape(escape_head, ch), escape_head);
+                            text.push(ch);
+                        }
+                        None => break,
+                    },
+                    Some(ch) => text.push(ch),
+                    None => break,
+                }
+            }
+            self.error(IllError::UnterminatedString(head), head);
+            TokenKind::Str(text)
+        }
+
+        fn next_token(&mut self) -> Option<Token> {
+            loop {
+                match self.peek()? {
+                    ch if ch.is_whitespace() => {
+                        self.bump();
+                    }
+                    COMMENT_SINGLE_LINE => {
+                        while self.peek().is_some_and(|x| x != NEWLINE) {
+                            self.bump();
+                        }
+                    }
+                    ch if ch != QUOTE && !SYMBOLS.contains(ch) && !is_word_char(ch) => {
+                        let head = ReadHead { line: self.line, column: self.column };
+                        self.error(IllError::UnexpectedCharacter(head, ch, None), head);
+                        self.bump();
+                    }
+                    _ => break,
+                }
+            }
+            let (start, line, column) = (self.offset(), self.line, self.column);
+            let head = ReadHead { line, column };
+            let ch = self.bump()?;
+            let kind = if ch == QUOTE {
+                self.string(head)
+            } else if SYMBOLS.contains(ch) {
+                TokenKind::Symbol(ch)
+            } else {
+                let mut word = ch.to_string();
+                while let Some(x) = self.peek().filter(|x| is_word_char(*x)) {
+                    word.push(x);
+                    self.bump();
+                }
+                // `3`, `0.5` and `.5` are Numbers, anything else that starts with a digit is left for the parser to reject
+                let numeric = ch.is_ascii_digit() || (ch == '.' && word.chars().nth(1).is_some_and(|x| x.is_ascii_digit()));
+                match word.parse::<f64>() {
+                    Ok(n) if numeric => TokenKind::Number(n),
+                    _ => TokenKind::Word(word),
+                }
+            };
+            Some(Token { kind, span: Span { start, end: self.offset(), line, column } })
+        }
+    }
+
+    /// Splits a whole file into tokens, dropping whitespace and `>` comments, along with everything that couldn't be
+    /// read: characters that start no token are skipped, and a bad escape is kept as the character it escaped. Nothing
+    /// else reads source text, short of `token_at`.
+    pub fn tokenize(file: &EnhancedFile) -> (Vec<Token>, Vec<AdvancedIllError>) {
+        let mut lexer = Lexer { file, pos: 0, line: 1, column: 1, errors: Vec::new() };
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        (tokens, lexer.errors)
+    }
+
+    /// The token that starts at byte `start`, which is at `head`, if one does; only that token is read.
+    pub fn token_at(file: &EnhancedFile, start: usize, head: ReadHead) -> Option<Token> {
+        let mut lexer = Lexer { file, pos: start, line: head.line, column: head.column, errors: Vec::new() };
+        lexer.next_token().filter(|x| x.span.start == start)
+    }
+
+    /// The tokens of one file, read front to back.
+    pub struct Tokens<'a> {
+        file: &'a EnhancedFile,
+        tokens: Vec<Token>,
+        pos: usize,
+    }
+
+    impl<'a> Iterator for Tokens<'a> {
+        type Item = Token;
+
+        fn next(&mut self) -> Option<Token> {
+            let token = self.tokens.get(self.pos).cloned();
+            self.pos += 1;
+            token
+        }
+    }
+
+    impl<'a> Tokens<'a> {
+        pub fn new(file: &'a EnhancedFile, tokens: Vec<Token>) -> Tokens<'a> {
+            Tokens { file, tokens, pos: 0 }
+        }
+
+        pub fn peek(&self) -> Option<&Token> {
+            self.tokens.get(self.pos)
+        }
+
+        /// Takes the next token if it's the symbol `ch`.
+        pub fn eat(&mut self, ch: char) -> Option<Token> {
+            if self.peek().is_some_and(|x| x.is_symbol(ch)) {
+                return self.next();
+            }
+            None
+        }
+
+        pub fn expect(&mut self, ch: char, expected: &str) -> Result<Token, AdvancedIllError> {
+            self.eat(ch).ok_or_else(|| self.unexpected(expected))
+        }
+
+        pub fn expect_word(&mut self, expected: &str) -> Result<(String, Span), AdvancedIllError> {
+            match self.peek().map(|x| (x.word().map(String::from), x.span)) {
+                Some((Some(word), span)) => {
+                    self.pos += 1;
+                    Ok((word, span))
+                }
+                _ => Err(self.unexpected(expected)),
+            }
+        }
+
+        /// Just past the last token, for errors about something missing at the end of the file.
+        pub fn end(&self) -> Span {
+            let last = match self.tokens.last() {
+                Some(token) => token.span,
+                None => return Span { line: 1, column: 1, ..Span::default() },
+            };
+            let end = last.end_head(self.file);
+            Span { start: last.end, end: last.end, line: end.line, column: end.column }
+        }
+
+        /// `UnexpectedToken` for the next token (or the end of the file), which should have been `expected`.
+        pub fn unexpected(&self, expected: &str) -> AdvancedIllError {
+            let (found, head) = match self.peek() {
+                Some(token) => (token.to_string(), token.span.head()),
+                None => (String::from("the end of the file"), self.end().head()),
+            };
+            AdvancedIllError::new(IllError::UnexpectedToken(head, found, String::from(expected)), Some(head), self.file.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use interpreter::ill::{EnhancedFile, IllError, ReadHead};
+    use super::ill::{tokenize, Span, TokenKind};
+
+    fn lex(source: &str) -> (Vec<(TokenKind, Span)>, Vec<IllError>) {
+        let (tokens, errors) = tokenize(&EnhancedFile::new("test.ill", source));
+        (tokens.into_iter().map(|x| (x.kind, x.span)).collect(), errors.into_iter().map(|x| *x.error).collect())
+    }
+
+    fn span(start: usize, end: usize, line: i32, column: i32) -> Span {
+        Span { start, end, line, column }
+    }
+
+    #[test]
+    fn spans() {
+        // `é` is two bytes but one column
+        let (tokens, errors) = lex("+é=-2.5;\n> a comment\n  $f(x) { ptl \"a\nb\"; }");
+        assert!(errors.is_empty());
+        assert_eq!(tokens, vec![
+            (TokenKind::Symbol('+'), span(0, 1, 1, 1)),
+            (TokenKind::Word(String::from("é")), span(1, 3, 1, 2)),
+            (TokenKind::Symbol('='), span(3, 4, 1, 3)),
+            (TokenKind::Symbol('-'), span(4, 5, 1, 4)),
+            (TokenKind::Number(2.5), span(5, 8, 1, 5)),
+            (TokenKind::Symbol(';'), span(8, 9, 1, 8)),
+            (TokenKind::Symbol('$'), span(24, 25, 3, 3)),
+            (TokenKind::Word(String::from("f")), span(25, 26, 3, 4)),
+            (TokenKind::Symbol('('), span(26, 27, 3, 5)),
+            (TokenKind::Word(String::from("x")), span(27, 28, 3, 6)),
+            (TokenKind::Symbol(')'), span(28, 29, 3, 7)),
+            (TokenKind::Symbol('{'), span(30, 31, 3, 9)),
+            (TokenKind::Word(String::from("ptl")), span(32, 35, 3, 11)),
+            (TokenKind::Str(String::from("a\nb")), span(36, 41, 3, 15)),
+            (TokenKind::Symbol(';'), span(41, 42, 4, 3)),
+            (TokenKind::Symbol('}'), span(43, 44, 4, 5)),
+        ]);
+    }
+
+    #[test]
+    fn escapes() {
+        let (tokens, errors) = lex(r#""\n\t\r\0\\\"\;""#);
+        assert!(errors.is_empty());
+        assert_eq!(tokens[0].0, TokenKind::Str(String::from("\n\t\r\0\\\";")));
+    }
+
+    #[test]
+    fn invalid_escape() {
+        let (tokens, errors) = lex("\"a\\qb\"");
+        assert_eq!(tokens[0].0, TokenKind::Str(String::from("aqb")));
+        match errors[..] {
+            [IllError::InvalidEscape(ReadHead { line: 1, column: 3 }, 'q')] => {}
+            ref other => panic!("unexpected errors: {:?}", other.iter().map(|x| x.to_string()).collect::<Vec<_>>()),
+        }
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let (tokens, errors) = lex("ptl \"never ends;\n}");
+        assert_eq!(tokens[1], (TokenKind::Str(String::from("never ends;\n}")), span(4, 18, 1, 5)));
+        match errors[..] {
+            [IllError::UnterminatedString(ReadHead { line: 1, column: 5 })] => {}
+            ref other => panic!("unexpected errors: {:?}", other.iter().map(|x| x.to_string()).collect::<Vec<_>>()),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+extern crate time;
+extern crate either;
+extern crate list;
+extern crate termcolor;
+
+pub mod interpreter;
+pub mod opcodes;
+pub mod builtins;
+pub mod files;
+pub mod expression;
+pub mod lexer;
+pub mod ast;
+pub mod parser;
+pub mod stdlib;
+pub mod diagnostic;
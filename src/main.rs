@@ -1,129 +1,147 @@
+#[macro_use]
 extern crate clap;
-extern crate time;
 extern crate termcolor;
-extern crate pcre;
-extern crate either;
-extern crate gag;
+extern crate pill;
 
 use clap::{Arg, App};
 use time::Duration;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use std::env;
 use std::fs::File;
-use std::io::Write;
-mod interpreter;
-use interpreter::ill::{Interpreter, AdvancedIllError};
-mod opcodes;
-
-
+use std::io::{Read, Write};
+use pill::diagnostic::ill::Diagnostic;
+use pill::interpreter::ill::{Interpreter, ExecutionResult, EXIT_PARSE_ERROR};
+use pill::opcodes;
 
-pub struct NamedFile {
-    file: File,
-    name: String,
-}
+extern crate time;
 
-fn repeat(times: i32, char: char) -> String {
-    let mut buff: String = String::new();
-    for _ in 0..times {
-        buff.push(char);
+fn read_source(path: &str) -> String {
+    let mut content = String::new();
+    match File::open(path).and_then(|mut file| file.read_to_string(&mut content)) {
+        Ok(_) => content,
+        Err(err) => {
+            let mut out = StandardStream::stderr(ColorChoice::Always);
+            out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan))).ok();
+            write!(&mut out, "Cannot read {:?}: ", path).ok();
+            out.set_color(ColorSpec::new().set_fg(Some(Color::White))).ok();
+            writeln!(&mut out, "{}", err).ok();
+            std::process::exit(EXIT_PARSE_ERROR);
+        }
     }
-    buff
 }
 
-
 fn main() {
+    // everything after `--` belongs to the program, not to us
+    let mut cli_args: Vec<String> = env::args().collect();
+    let program_args = match cli_args.iter().position(|x| x == "--") {
+        Some(pos) => {
+            let program_args = cli_args.split_off(pos + 1);
+            cli_args.pop();
+            program_args
+        }
+        None => Vec::new(),
+    };
     let arg_matches = App::new("ill interpreter")
         .version("0.8F")
         .author("haze booth <admin@haze.pw>")
         .about("the (pretty) ill tiny language interpreter")
         .arg(
             Arg::with_name("inputs")
-                .help("the ill source files")
+                .help("the ill source files; anything after `--` is passed to the program")
                 .required(true)
                 .multiple(true),
         )
-        .arg(Arg::with_name("preamble").long("preamble").takes_value(true).short("pre").multiple(true).help("load these files before we execute the main ones."))
+        .arg(Arg::with_name("preamble").long("preamble").takes_value(true).short("pre").multiple(true).number_of_values(1).help("load these files before we execute the main ones."))
         .arg(Arg::with_name("debug").help("show debug text").short("d").long("debug"))
         .arg(Arg::with_name("quiet").help("only show program output").short("q").long("quiet"))
-        .get_matches();
+        .arg(Arg::with_name("max-depth").long("max-depth").takes_value(true).help("how deeply instruction calls may nest before giving up (default 1000)."))
+        .arg(Arg::with_name("allow-dir").long("allow-dir").takes_value(true).multiple(true).number_of_values(1).help("let the program open files inside this directory."))
+        .arg(Arg::with_name("std").long("std").help("make the standard library modules (math, logic, io) available without `use`."))
+        .arg(Arg::with_name("module-path").long("module-path").short("I").takes_value(true).multiple(true).number_of_values(1).help("look for used modules in this directory too."))
+        .arg(Arg::with_name("error-format").long("error-format").takes_value(true).possible_values(&["human", "json"]).default_value("human").help("how to show errors; `json` writes one object per line to stderr."))
+        .arg(Arg::with_name("max-errors").long("max-errors").takes_value(true).help("show at most this many errors (default all)."))
+        .arg(Arg::with_name("max-iterations").long("max-iterations").takes_value(true).help("fail any while loop that runs more than this many times."))
+        .get_matches_from(cli_args);
 
     let input_files_str: Vec<_> = arg_matches.values_of("inputs").unwrap().collect();
-    let preamble_files;
-    if arg_matches.is_present("preamble") {
-        let preamble_files_str: Vec<_> = arg_matches.values_of("preamble").unwrap().collect();
-        preamble_files = preamble_files_str
-            .iter()
-            .filter(|x| File::open(x).is_ok())
-            .map(|x| {
-                NamedFile {
-                    file: File::open(x).unwrap(),
-                    name: String::from(*x)
-                }
-            }).collect();
+    let preamble_files_str: Vec<_> = arg_matches.values_of("preamble").map(|x| x.collect::<Vec<_>>()).unwrap_or_default();
+    let quiet = arg_matches.is_present("quiet");
+    let json_errors = arg_matches.value_of("error-format") == Some("json");
+    let max_errors = if arg_matches.is_present("max-errors") {
+        Some(value_t!(arg_matches, "max-errors", usize).unwrap_or_else(|e| e.exit()))
     } else {
-        preamble_files = Vec::new();
+        None
+    };
+
+    let mut int: Interpreter = Interpreter::new(opcodes::ill::default_opcodes())
+        .debug(arg_matches.is_present("debug"))
+        .stream_output(true)
+        .std_library(arg_matches.is_present("std"))
+        .args(program_args);
+    if arg_matches.is_present("max-depth") {
+        int = int.max_call_depth(value_t!(arg_matches, "max-depth", usize).unwrap_or_else(|e| e.exit()));
     }
-    let input_files: Vec<_> = input_files_str
-        .iter()
-        .filter(|x| File::open(x).is_ok())
-        .map(|x| {
-            NamedFile {
-                file: File::open(x).unwrap(),
-                name: String::from(*x),
-            }
-        })
-        .collect();
-    let mut int: Interpreter = Interpreter::new(arg_matches.is_present("debug"), arg_matches.is_present("quiet"), input_files, preamble_files, opcodes::ill::default_opcodes());
-    let mut res: Option<AdvancedIllError> = None;
-    let dur = Duration::span(|| { res = int.begin_parsing(); });
+    if arg_matches.is_present("max-iterations") {
+        int = int.max_iterations(value_t!(arg_matches, "max-iterations", usize).unwrap_or_else(|e| e.exit()));
+    }
+    for dir in arg_matches.values_of("allow-dir").map(|x| x.collect::<Vec<_>>()).unwrap_or_default() {
+        int = int.allow_dir(dir);
+    }
+    for dir in arg_matches.values_of("module-path").map(|x| x.collect::<Vec<_>>()).unwrap_or_default() {
+        int = int.module_path(dir);
+    }
+    for name in preamble_files_str {
+        int = int.preamble(name, &read_source(name));
+    }
+    for name in input_files_str {
+        int = int.source(name, &read_source(name));
+    }
+    let mut result: Option<ExecutionResult> = None;
+    let dur = Duration::span(|| { result = Some(int.run()); });
+    let result = result.unwrap();
     let mut out = StandardStream::stdout(ColorChoice::Always);
 
-    if res.is_some() {
-        let err = res.unwrap();
-        out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))
-            .ok();
-        let err_head_line = if err.head.is_some() { err.head.unwrap().line } else { -1 };
-        let error_str = err.get_error_portion();
-        if error_str.is_some() {
-            let xstr = error_str.unwrap();
-            let head = err.head.unwrap();
-            let space_push_buffer = repeat(head.line.to_string().len() as i32, ' ');
-            writeln!(&mut out, "    {}{}", space_push_buffer, err.error.name()).ok();
-            print!("{}--> ", space_push_buffer);
-            out.set_color(ColorSpec::new().set_fg(Some(Color::White)))
-                .ok();
-            println!("{}:{}:{}", err.file.filename, head.line, head.column);
-            out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))
-                .ok();
-            for line in (err_head_line - 1)..(err_head_line + 2) {
-                out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))
-                    .ok();
-                if line == err_head_line {
-                    print!("{} |", line);
-                    out.set_color(ColorSpec::new().set_fg(Some(Color::White)))
-                        .ok();
-                    println!(" {}", xstr);
-                } else if line == (err_head_line + 1) {
-                    let err_pointer_buffer = repeat(head.column - 1, ' ');
-                    print!("{} |{}", line, err_pointer_buffer);
-                    let err_tail = repeat((xstr.len() as i32 - head.column), '-');
-                    print!(" ^{}", err_tail);
-                    out.set_color(ColorSpec::new().set_fg(Some(Color::White)))
-                        .ok();
-                    println!(" {}", err.error.get_actual_desc());
-                } else {
-                    println!("{} |", line);
-                }
+    if !quiet {
+        let timings = result.timings;
+        if let (Some(parsing), Some(execution)) = (timings.parsing, timings.execution) {
+            println!("Pill Main Instruction Execution took {}s ({}ms).", execution.num_seconds(), execution.num_milliseconds());
+            if let Some(preamble_scan) = timings.preamble_scan {
+                println!("Pill Preamble Instruction Scan took {}s, ({}ms).", preamble_scan.num_seconds(), preamble_scan.num_milliseconds());
             }
+            println!("Pill Main Instruction Parsing took ({}s, ({}ms).", parsing.num_seconds(), parsing.num_milliseconds());
+        } else {
+            println!("Encountered an error therefore any calculations of time are voided.");
+        }
+    }
+
+    let errors: Vec<Diagnostic> = result.errors.iter().map(Diagnostic::new).collect();
+    let shown = max_errors.map_or(errors.len(), |max| max.min(errors.len()));
+    for diag in errors.iter().take(shown) {
+        if json_errors {
+            eprintln!("{}", diag.to_json());
+        } else {
+            diag.render(&mut out).ok();
+        }
+    }
+    if !errors.is_empty() && !json_errors {
+        out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan))).ok();
+        write!(&mut out, "{} error{} found", errors.len(), if errors.len() == 1 { "" } else { "s" }).ok();
+        out.set_color(ColorSpec::new().set_fg(Some(Color::White))).ok();
+        if shown < errors.len() {
+            writeln!(&mut out, ", showing the first {}.", shown).ok();
+        } else {
+            writeln!(&mut out, ".").ok();
         }
-        out.set_color(ColorSpec::new().set_fg(Some(Color::White)))
-            .ok();
     }
 
-    if !int.quiet {
+    if !quiet {
         println!(
             "PILL Execution took: {}s, ({} ms)",
             dur.num_seconds(),
             dur.num_milliseconds()
         );
     }
+    // exiting skips destructors, so flush whatever the program printed without a newline
+    std::io::stdout().flush().ok();
+    std::process::exit(result.exit_code);
 }
\ No newline at end of file
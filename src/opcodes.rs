@@ -1,23 +1,31 @@
 pub mod ill {
-    use interpreter::ill::{ReadHead, Register, Instruction, EnhancedFile, AdvancedIllError, IllError};
+    use interpreter::ill::{ReadHead, Register, Value, Instruction, EnhancedFile, AdvancedIllError, IllError, Output, Input, EOF_REGISTER};
+    use files::ill::{FileTable, FileFailure};
     use opcodes::ill::ExpressionType::*;
+    use builtins::ill::*;
     use std::default::Default;
+    use std::fmt;
+    use std::cmp::Ordering;
+    use std::sync::Arc;
     use either::Either;
+    use list::List;
 
-    const TRUE: f64 = 0f64;
-    const FALSE: f64 = 1f64;
+    pub const TRUE: f64 = 0f64;
+    pub const FALSE: f64 = 1f64;
 
     #[derive(Debug, Clone)]
     pub enum ExpressionType {
         IntegerLiteral(f64),
-        ProbableLiteral(Either<f64, String>),
+        // a literal Number or String, or the name of a container holding one
+        ProbableLiteral(Either<Value, String>),
         StringLiteral(String),
         ContainerReference(String),
         // both stacks and variables, no difference (Will search current instruction before searching registers)
         RegisterReference(String),
         // Stack Name
         VariableReference(String),
-        InstructionReference(String, Vec<String>),
+        // instruction name, and the values passed to its parameters at the call site
+        InstructionReference(String, Vec<Either<Value, String>>),
     }
 
 
@@ -39,7 +47,7 @@ pub mod ill {
         ExpressionType::IntegerLiteral(0 as f64)
     }
 
-    pub fn prob_literal() -> ExpressionType { ExpressionType::ProbableLiteral(Either::Left(FALSE)) }
+    pub fn prob_literal() -> ExpressionType { ExpressionType::ProbableLiteral(Either::Left(Value::Number(FALSE))) }
 
     pub fn container() -> ExpressionType {
         ExpressionType::ContainerReference(String::new())
@@ -61,28 +69,357 @@ pub mod ill {
 
 
     // i've always wanted a modular language...
-    pub fn default_opcodes() -> Vec<OpCode> {
-        let mut opcodes: Vec<OpCode> = Vec::new();
-        opcodes.push(OpCode::new("mov").expecting(prob_literal()).expecting(container()));
-        opcodes.push(OpCode::new("mod").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal()));
-        opcodes.push(OpCode::new("gt").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal()));
-        opcodes.push(OpCode::new("lt").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal()));
-        opcodes.push(OpCode::new("eq").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal()));
-        opcodes.push(OpCode::new("gte").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal()));
-        opcodes.push(OpCode::new("lte").expecting(prob_literal()).expecting(prob_literal()).expecting(s_literal()));
-        opcodes.push(OpCode::new("add").expecting(prob_literal()).expecting(container()));
-        opcodes.push(OpCode::new("mak").expecting(s_literal()).expecting(prob_literal()));
-        opcodes.push(OpCode::new("dis").expecting(container()));
-        opcodes.push(OpCode::new("dsl").expecting(container()));
-        opcodes.push(OpCode::new("do").expecting(inst_ref()));
-        opcodes.push(OpCode::new("dor").expecting(inst_ref()).expecting(s_literal()));
-        opcodes.push(OpCode::new("del").expecting(variable()));
-        opcodes.push(OpCode::new("pt").expecting(s_literal()));
-        opcodes.push(OpCode::new("ptl").expecting(s_literal()));
-        opcodes.push(OpCode::new("neg").expecting(container()));
-        opcodes.push(OpCode::new("for").expecting(s_literal()).expecting(literal()).expecting(literal()).expecting(literal()).expecting(inst_ref()));
-        opcodes.push(OpCode::new("if").expecting(inst_ref()).expecting(inst_ref()).expecting(inst_ref()));
-        opcodes
+    pub fn default_opcodes() -> OpCodeRegistry {
+        OpCodeRegistry::new()
+            .register(Mov)
+            .register(Mod)
+            .register(Comparison::new("gt", |o| o == Ordering::Greater))
+            .register(Comparison::new("lt", |o| o == Ordering::Less))
+            .register(Comparison::new("eq", |o| o == Ordering::Equal))
+            .register(Comparison::new("gte", |o| o != Ordering::Less))
+            .register(Comparison::new("lte", |o| o != Ordering::Greater))
+            .register(Arithmetic::new("add", |a, b| a + b))
+            .register(Arithmetic::new("sub", |a, b| a - b))
+            .register(Arithmetic::new("mul", |a, b| a * b))
+            .register(Arithmetic::dividing("div", |a, b| a / b))
+            .register(Arithmetic::new("pow", f64::powf))
+            .register(Arithmetic::new("min", f64::min))
+            .register(Arithmetic::new("max", f64::max))
+            .register(UnaryArithmetic::new("abs", f64::abs))
+            .register(UnaryArithmetic::new("flr", f64::floor))
+            .register(UnaryArithmetic::new("cel", f64::ceil))
+            .register(Mak::new("mak", false))
+            .register(Mak::new("mkc", true))
+            .register(Display::new("dis", false))
+            .register(Display::new("dsl", true))
+            .register(Do)
+            .register(Dor)
+            .register(Del)
+            .register(Print::new("pt", false))
+            .register(Print::new("ptl", true))
+            .register(Neg)
+            .register(For::exclusive("for"))
+            .register(For::inclusive("fore"))
+            .register(If)
+            .register(While)
+            .register(LoopControl::new("brk", Flow::Break))
+            .register(LoopControl::new("cnt", Flow::Continue))
+            .register(Ret)
+            .register(Exit)
+            .register(Read::new("rdn", ReadKind::Number))
+            .register(Read::new("rdl", ReadKind::Line))
+            .register(Read::new("rdc", ReadKind::Char))
+            .register(Opn)
+            .register(Rdf)
+            .register(FileWrite::new("wrt", false))
+            .register(FileWrite::new("wrl", true))
+            .register(Cls)
+            .register(Cat)
+            .register(Len)
+            .register(Sbs)
+            .register(Num)
+            .register(Str)
+            .register(Lst)
+            .register(Psh)
+            .register(Pop)
+            .register(Idx)
+            .register(Set)
+            .register(Each)
+    }
+
+    /// Implemented once per opcode. `signature` describes the name and argument shapes the parser checks calls
+    /// against, and `execute` runs a parsed call of it. Handlers are shared between threads along with the `Interpreter`
+    /// holding them, so they must be `Send` and `Sync`.
+    pub trait OpCodeHandler: Send + Sync {
+        fn signature(&self) -> OpCode;
+        fn execute(&self, code: &OpCode, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError>;
+    }
+
+    /// Where execution goes after an opcode has run.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Flow {
+        // carry on with the next opcode
+        Next,
+        // leave the innermost loop
+        Break,
+        // skip to the next iteration of the innermost loop
+        Continue,
+        // leave the current instruction, `res` already holds the result; only an `if` passes it on from its branch
+        Return,
+        // stop the whole program with this exit code
+        Exit(i32),
+    }
+
+    /// The set of opcodes an `Interpreter` understands. Registering a name twice replaces the earlier handler,
+    /// so built-ins can be overridden.
+    #[derive(Default, Clone)]
+    pub struct OpCodeRegistry {
+        handlers: Vec<(OpCode, Arc<dyn OpCodeHandler>)>,
+    }
+
+    impl OpCodeRegistry {
+        pub fn new() -> OpCodeRegistry {
+            OpCodeRegistry::default()
+        }
+
+        pub fn register<H: OpCodeHandler + 'static>(mut self, handler: H) -> OpCodeRegistry {
+            let signature = handler.signature();
+            self.handlers.retain(|(x, _)| x.name != signature.name);
+            self.handlers.push((signature, Arc::new(handler)));
+            self
+        }
+
+        pub fn find_signature(&self, name: &str) -> Option<&OpCode> {
+            self.handlers.iter().find(|(x, _)| x.name == name).map(|(x, _)| x)
+        }
+
+        pub fn find_handler(&self, name: &str) -> Option<Arc<dyn OpCodeHandler>> {
+            self.handlers.iter().find(|(x, _)| x.name == name).map(|(_, h)| h.clone())
+        }
+
+        pub fn signatures(&self) -> Vec<&OpCode> {
+            self.handlers.iter().map(|(x, _)| x).collect()
+        }
+    }
+
+    impl fmt::Debug for OpCodeRegistry {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_list().entries(self.signatures()).finish()
+        }
+    }
+
+    /// Everything an executing opcode can see and touch: the global registers, the frame of the running
+    /// instruction, program output and the other instructions it may call.
+    pub struct ExecutionContext<'a> {
+        pub file: &'a EnhancedFile,
+        pub debug: bool,
+        pub registers: &'a mut Vec<Register>,
+        // the current call frame; every invocation gets its own, starting with `res` and the bound parameters
+        pub scope: &'a mut Vec<Register>,
+        pub output: &'a mut Output,
+        pub input: &'a mut Input,
+        pub files: &'a mut FileTable,
+        pub instructions: &'a [Instruction],
+        pub opcodes: &'a OpCodeRegistry,
+        pub depth: usize,
+        pub max_depth: usize,
+        // how many times a single `while` may loop, if limited at all
+        pub max_iterations: Option<usize>,
+        // whether this frame runs, however indirectly, as the body of a loop, which `brk` and `cnt` need
+        pub in_loop: bool,
+    }
+
+    impl<'a> ExecutionContext<'a> {
+        pub fn error(&self, err: IllError, head: ReadHead) -> AdvancedIllError {
+            AdvancedIllError::new(err, Some(head), self.file.clone())
+        }
+
+        pub fn register_exists(&self, name: &str) -> bool {
+            self.registers.iter().any(|x| x.identifier == name)
+        }
+
+        /// Whether a new variable called `name` would clash with a register. Inside a module none do, since a module's
+        /// own registers all have qualified names and its variables may shadow any of the program's.
+        pub fn clashes_with_register(&self, name: &str) -> bool {
+            self.file.module.is_none() && self.register_exists(name)
+        }
+
+        pub fn variable_exists(&self, name: &str) -> bool {
+            self.scope.iter().any(|x| x.identifier == name)
+        }
+
+        /// Looks a container up for reading, in the current scope first, then in the global registers.
+        pub fn lookup(&self, head: ReadHead, name: &str) -> Result<&Register, AdvancedIllError> {
+            match self.scope.iter().chain(self.registers.iter()).find(|x| x.identifier == name) {
+                Some(reg) => Ok(reg),
+                None => Err(self.error(IllError::NonExistentRegister(head, String::from(name)), head)),
+            }
+        }
+
+        /// Resolves a probable literal: either the literal itself, or the value of the container it names.
+        pub fn value(&self, head: ReadHead, ei: &Either<Value, String>) -> Result<Value, AdvancedIllError> {
+            match *ei {
+                Either::Left(ref value) => Ok(value.clone()),
+                Either::Right(ref name) => self.lookup(head, name).map(|x| x.value.clone()),
+            }
+        }
+
+        /// Like `value`, but insists on a Number.
+        pub fn number(&self, head: ReadHead, ei: &Either<Value, String>) -> Result<f64, AdvancedIllError> {
+            match self.value(head, ei)? {
+                Value::Number(n) => Ok(n),
+                other => Err(self.error(IllError::TypeMismatch(head, String::from("Number"), other.type_name()), head)),
+            }
+        }
+
+        /// Like `value`, but insists on a String.
+        pub fn string(&self, head: ReadHead, ei: &Either<Value, String>) -> Result<String, AdvancedIllError> {
+            match self.value(head, ei)? {
+                Value::Str(s) => Ok(s),
+                other => Err(self.error(IllError::TypeMismatch(head, String::from("String"), other.type_name()), head)),
+            }
+        }
+
+        /// Like `value`, but insists on a File Handle.
+        pub fn handle(&self, head: ReadHead, ei: &Either<Value, String>) -> Result<usize, AdvancedIllError> {
+            match self.value(head, ei)? {
+                Value::Handle(handle) => Ok(handle),
+                other => Err(self.error(IllError::TypeMismatch(head, String::from("File Handle"), other.type_name()), head)),
+            }
+        }
+
+        /// Turns a failed file operation on `path` into the matching error.
+        pub fn file_error(&self, head: ReadHead, path: &str, failure: FileFailure) -> AdvancedIllError {
+            match failure {
+                FileFailure::NotAllowed => self.error(IllError::PathNotAllowed(head, String::from(path)), head),
+                FileFailure::Io(msg) => self.error(IllError::FileError(head, format!("{}: {}", path, msg)), head),
+            }
+        }
+
+        /// Looks a container up in the current scope first, then in the global registers, so it can be changed.
+        /// Read-only registers raise `ImmutableRegister`.
+        pub fn container(&mut self, head: ReadHead, name: &str) -> Result<&mut Register, AdvancedIllError> {
+            if self.lookup(head, name)?.is_constant {
+                return Err(self.error(IllError::ImmutableRegister(head, String::from(name)), head));
+            }
+            let scope = &mut *self.scope;
+            let registers = &mut *self.registers;
+            Ok(scope.iter_mut().chain(registers.iter_mut()).find(|x| x.identifier == name).unwrap())
+        }
+
+        /// Replaces the Number held by a container with `f` applied to it.
+        pub fn update_number<F: FnOnce(f64) -> f64>(&mut self, head: ReadHead, name: &str, f: F) -> Result<(), AdvancedIllError> {
+            let current = self.container(head, name)?.value.clone();
+            match current {
+                Value::Number(n) => {
+                    self.container(head, name)?.value = Value::Number(f(n));
+                    Ok(())
+                }
+                other => Err(self.error(IllError::TypeMismatch(head, String::from("Number"), other.type_name()), head)),
+            }
+        }
+
+        /// The List held by a container, for reading.
+        pub fn read_list(&self, head: ReadHead, name: &str) -> Result<&List<Value>, AdvancedIllError> {
+            match self.lookup(head, name)?.value {
+                Value::List(ref list) => Ok(list),
+                ref other => Err(self.error(IllError::TypeMismatch(head, String::from("List"), other.type_name()), head)),
+            }
+        }
+
+        /// The List held by a container, ready to be changed in place.
+        pub fn list(&mut self, head: ReadHead, name: &str) -> Result<&mut List<Value>, AdvancedIllError> {
+            let type_name = self.container(head, name)?.value.type_name();
+            if type_name != "List" {
+                return Err(self.error(IllError::TypeMismatch(head, String::from("List"), type_name), head));
+            }
+            match self.container(head, name)?.value {
+                Value::List(ref mut list) => Ok(list),
+                _ => unreachable!(),
+            }
+        }
+
+        /// Records whether the last read ran out of input.
+        pub fn set_eof(&mut self, eof: bool) {
+            if let Some(reg) = self.registers.iter_mut().find(|x| x.identifier == EOF_REGISTER) {
+                reg.value = Value::Number(if eof { TRUE } else { FALSE });
+            }
+        }
+
+        /// Like `declare`, but the variable can never be changed or deleted.
+        pub fn declare_constant<V: Into<Value>>(&mut self, head: ReadHead, name: &str, value: V) -> Result<(), AdvancedIllError> {
+            self.declare(head, name, value)?;
+            self.scope.last_mut().unwrap().is_constant = true;
+            Ok(())
+        }
+
+        /// Introduces a new variable into the current scope, refusing to shadow anything that already exists (but see
+        /// `clashes_with_register`).
+        pub fn declare<V: Into<Value>>(&mut self, head: ReadHead, name: &str, value: V) -> Result<(), AdvancedIllError> {
+            if self.clashes_with_register(name) {
+                let err = IllError::RegisterRedefinition(head, String::from(name), Some(register().name()));
+                return Err(self.error(err, head));
+            } else if self.variable_exists(name) {
+                let err = IllError::RegisterRedefinition(head, String::from(name), Some(variable().name()));
+                return Err(self.error(err, head));
+            }
+            self.scope.push(Register { identifier: String::from(name), value: value.into(), is_variable: true, ..Default::default() });
+            Ok(())
+        }
+
+        /// Runs the named instruction in a fresh frame with its parameters bound to `args`, and hands back its `res`
+        /// along with any `brk`, `cnt` or `exit` that escaped it. `injected` adds extra variables to the new frame
+        /// (e.g. the counter of a `for`).
+        pub fn call_with(&mut self, head: ReadHead, name: &str, args: &[Either<Value, String>], injected: &[(&str, Value)]) -> Result<(Value, Flow), AdvancedIllError> {
+            let in_loop = self.in_loop;
+            self.call(head, name, args, injected, in_loop).map(returned)
+        }
+
+        /// Like `call_with`, for the body of a loop, where `brk` and `cnt` may be used.
+        pub fn call_body(&mut self, head: ReadHead, name: &str, args: &[Either<Value, String>], injected: &[(&str, Value)]) -> Result<(Value, Flow), AdvancedIllError> {
+            self.call(head, name, args, injected, true).map(returned)
+        }
+
+        /// Like `call_with`, but a `ret` in the called instruction comes back as `Flow::Return`, for an `if` to leave the
+        /// instruction it's in as well.
+        pub fn call_branch(&mut self, head: ReadHead, name: &str, args: &[Either<Value, String>]) -> Result<(Value, Flow), AdvancedIllError> {
+            let in_loop = self.in_loop;
+            self.call(head, name, args, &[], in_loop)
+        }
+
+        fn call(&mut self, head: ReadHead, name: &str, args: &[Either<Value, String>], injected: &[(&str, Value)], in_loop: bool) -> Result<(Value, Flow), AdvancedIllError> {
+            let instructions = self.instructions;
+            let inst = match instructions.iter().find(|x| x.name == name) {
+                Some(inst) => inst,
+                None => return Err(self.error(IllError::NonExistentInstruction(head, String::from(name)), head)),
+            };
+            if inst.arguments.len() != args.len() {
+                let err = IllError::OpCodeArgumentMismatch(head, String::from(name), inst.arguments.len() as i32, args.len() as i32);
+                return Err(self.error(err, head));
+            }
+            if self.depth >= self.max_depth {
+                let err = IllError::MaximumRecursionDepth(head, String::from(name), self.max_depth as i32);
+                return Err(self.error(err, head));
+            }
+            let mut frame = inst.scope.clone();
+            for (param, arg) in inst.arguments.iter().zip(args) {
+                // injected variables can be passed straight on, e.g. `for i 0 10 1 body(i)`
+                let value = match *arg {
+                    Either::Right(ref name) if injected.iter().any(|&(x, _)| x == name) => {
+                        injected.iter().find(|&&(x, _)| x == name).unwrap().1.clone()
+                    }
+                    _ => self.value(head, arg)?,
+                };
+                frame.push(Register { identifier: param.clone(), value, is_variable: true, ..Default::default() });
+            }
+            for &(identifier, ref value) in injected {
+                frame.push(Register { identifier: String::from(identifier), value: value.clone(), is_variable: true, ..Default::default() });
+            }
+            let mut callee = ExecutionContext {
+                file: &inst.file,
+                debug: self.debug,
+                registers: &mut *self.registers,
+                scope: &mut frame,
+                output: &mut *self.output,
+                input: &mut *self.input,
+                files: &mut *self.files,
+                instructions: self.instructions,
+                opcodes: self.opcodes,
+                depth: self.depth + 1,
+                max_depth: self.max_depth,
+                max_iterations: self.max_iterations,
+                in_loop,
+            };
+            inst.c_execute(&mut callee)
+        }
+    }
+
+    // a `ret` ends with the instruction it was in
+    fn returned((value, flow): (Value, Flow)) -> (Value, Flow) {
+        match flow {
+            Flow::Return => (value, Flow::Next),
+            flow => (value, flow),
+        }
     }
 
     #[derive(Default, Debug, Clone)]
@@ -90,6 +427,10 @@ pub mod ill {
         pub name: String,
         pub arguments: Vec<ExpressionType>,
         pub location: Option<ReadHead>,
+        // in a signature, the index from which arguments may be left out
+        pub optional_from: Option<usize>,
+        // in a signature, the indices of the containers the opcode changes
+        pub mutated: Vec<usize>,
     }
 
 
@@ -116,519 +457,42 @@ pub mod ill {
             }
         }
 
-        fn instruction_exists(&self, name: &String, insts: Vec<Instruction>) -> bool {
-            insts.iter().find(|x| x.name == *name).is_some()
+        /// Like `expecting`, but the opcode changes the container passed here, so constants are turned away.
+        pub fn mutating(mut self, some: ExpressionType) -> OpCode {
+            self.mutated.push(self.arguments.len());
+            self.expecting(some)
         }
 
-        fn get_absolute_value(&self, file: EnhancedFile, rh_err: ReadHead, ei: &Either<f64, String>, registers: &Vec<Register>, scope: &mut Vec<Register>) -> Result<f64, AdvancedIllError> {
-            let is_left = ei.is_left();
-            let v_clone = ei.clone();
-            Ok(if is_left { v_clone.left().unwrap() } else {
-                let name = v_clone.right().unwrap();
-                if !self.g_register_exists(name.clone(), registers) {
-                    if !self.l_register_exists(name.clone(), scope) {
-                        let err = IllError::NonExistentRegister(rh_err, name.clone());
-                        return Err(AdvancedIllError::new(err, Some(rh_err), file));
-                    } else {
-                        scope.iter_mut().find(|x| x.identifier == name).unwrap().value
-                    }
-                } else {
-                    registers.iter().find(|x| x.identifier == name).unwrap().value
-                }
-            })
+        pub fn mutates(&self, index: usize) -> bool {
+            self.mutated.contains(&index)
         }
 
-        fn register_exists(&self, name: String, global: bool, registers: Option<&Vec<Register>>, scope: Option<&mut Vec<Register>>) -> bool {
-            if global {
-                return registers.unwrap().iter().find(|x| x.identifier == *name).is_some();
-            } else {
-                return scope.unwrap().iter().find(|x| x.identifier == *name).is_some();
+        /// Like `expecting`, but the argument (and any after it) may be left out.
+        pub fn optionally(self, some: ExpressionType) -> OpCode {
+            let optional_from = self.optional_from.unwrap_or(self.arguments.len());
+            OpCode {
+                optional_from: Some(optional_from),
+                ..self.expecting(some)
             }
         }
-        fn g_register_exists(&self, name: String, g_registers: &Vec<Register>) -> bool { self.register_exists(name, true, Some(g_registers), None) }
-        fn l_register_exists(&self, name: String, scope: &mut Vec<Register>) -> bool { self.register_exists(name, false, None, Some(scope)) }
 
+        /// The fewest arguments a call of this signature may have.
+        pub fn required_arguments(&self) -> usize {
+            self.optional_from.unwrap_or(self.arguments.len())
+        }
 
-        pub fn execute(&self, file: EnhancedFile, debug: bool, registers: &mut Vec<Register>, mut o_insts: Vec<Instruction>, scope: &mut Vec<Register>) -> Result<(), AdvancedIllError> {
-            let rh_err: ReadHead = self.location.unwrap().clone();
-            let rh_err_o: Option<ReadHead> = Some(rh_err);
-            fn get_and_execute(file: EnhancedFile, name: &String, debug: bool, registers: &mut Vec<Register>, mut insts: Vec<Instruction>, scope: &mut Vec<Register>) -> Result<f64, AdvancedIllError> {
-                let clone = insts.clone();
-                /* let f_clone = file.try_clone();
-                if f_clone.is_some() {
-                    Ok(insts.iter_mut().find(|x| x.name == *name).unwrap().c_execute(file.unsafe_clone(), debug, registers, clone, scope));
-                }
-                Err(f_clone.err()) */
-                insts.iter_mut().find(|x| x.name == *name).unwrap().c_execute(file.unsafe_clone(), debug, registers, clone, scope)
-            }
-            match &*self.name.to_lowercase() {
-                "mak" => {
-                    if let ExpressionType::StringLiteral(ref identifier) = self.arguments[0] {
-                        if self.g_register_exists(identifier.clone(), registers) {
-                            let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                        } else if self.l_register_exists(identifier.clone(), scope) {
-                            let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                        }
-                        if let ExpressionType::ProbableLiteral(ref value) = self.arguments[1] {
-                            if identifier.eq_ignore_ascii_case("res") {
-                                let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(format!("default register {:?}", identifier)));
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-                            }
-                            let cont = self.get_absolute_value(file.unsafe_clone(), rh_err, value, registers, scope);
-                            if cont.is_err() {
-                                return Err(cont.err().unwrap());
-                            }
-                            scope.push(Register { identifier: identifier.clone(), value: cont.ok().unwrap(), is_variable: true });
-                            if debug {
-                                println!("Added variable {} => {}", identifier, value);
-                            }
-                        }
-                    }
-                }
-                "neg" => {
-                    if let ExpressionType::ContainerReference(ref value) = self.arguments[0] {
-                        let reg_ref = if !self.g_register_exists(value.clone(), registers) {
-                            if !self.l_register_exists(value.clone(), scope) {
-                                let err = IllError::NonExistentRegister(rh_err, value.clone());
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                            } else {
-                                scope.iter_mut().find(|x| x.identifier == *value).unwrap()
-                            }
-                        } else {
-                            registers.iter_mut().find(|x| x.identifier == *value).unwrap()
-                        };
-                        if reg_ref.value == TRUE {
-                            reg_ref.value = FALSE;
-                        } else {
-                            reg_ref.value = TRUE;
-                        }
-                    }
-                }
-                "add" => {
-                    if let ExpressionType::ProbableLiteral(ref value) = self.arguments[0] {
-                        if let ExpressionType::ContainerReference(ref variable) = self.arguments[1] {
-                            let value = self.get_absolute_value(file.unsafe_clone(), rh_err, value, registers, scope);
-                            if value.is_err() {
-                                return Err(value.err().unwrap());
-                            }
-                            let res = value.ok().unwrap();
-                            if self.l_register_exists(variable.clone(), scope) {
-                                let reg = scope.iter_mut().find(|x| x.identifier == *variable).unwrap();
-                                reg.value += res;
-                            } else if self.g_register_exists(variable.clone(), registers) {
-                                let reg = registers.iter_mut().find(|x| x.identifier == *variable).unwrap();
-                                reg.value += res;
-                            } else {
-                                let err = IllError::NonExistentRegister(rh_err, variable.clone());
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-                            }
-                        }
-                    }
-                }
-                "mov" => {
-                    if let ExpressionType::ProbableLiteral(ref value_x) = self.arguments[0] {
-                        if let ExpressionType::ContainerReference(ref identifier) = self.arguments[1] {
-                            let value = self.get_absolute_value(file.unsafe_clone(), rh_err, value_x, registers, scope);
-                            if value.is_err() {
-                                return Err(value.err().unwrap());
-                            }
-                            let val = value.ok().unwrap();
-                            if !self.g_register_exists(identifier.clone(), registers) {
-                                if !self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::NonExistentRegister(rh_err, identifier.clone()); // Error is implemented but will never be thrown because the it wont compile if the register doesnt exist
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                } else {
-                                    let reg = scope.iter_mut().find(|x| x.identifier == *identifier).unwrap();
-                                    reg.value = val;
-                                }
-                            } else {
-                                if debug {
-                                    println!("Moved {} onto {}.", val, identifier);
-                                }
-                                let reg = registers.iter_mut().find(|x| x.identifier == *identifier).unwrap();
-                                reg.value = val;
-                            }
-                        }
-                    }
-                }
-                "dsl" => {
-                    if let ExpressionType::ContainerReference(ref identifier) = self.arguments[0] {
-                        let value;
-                        if !self.g_register_exists(identifier.clone(), registers) {
-                            if !self.l_register_exists(identifier.clone(), scope) {
-                                let err = IllError::NonExistentRegister(rh_err, identifier.clone());
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-                            } else {
-                                value = scope.iter().find(|x| x.identifier == *identifier).unwrap().value;
-                            }
-                        } else {
-                            value = registers.iter().find(|x| x.identifier == *identifier).unwrap().value;
-                        }
-                        println!("{}", value);
-                    }
-                }
-                "dis" => {
-                    if let ExpressionType::ContainerReference(ref identifier) = self.arguments[0] {
-                        let value;
-                        if !self.g_register_exists(identifier.clone(), registers) {
-                            if !self.l_register_exists(identifier.clone(), scope) {
-                                let err = IllError::NonExistentRegister(rh_err, identifier.clone());
-                                let adv_err = Err(AdvancedIllError::new(err, rh_err_o, file));
-                                return adv_err;
-                            } else {
-                                value = scope.iter().find(|x| x.identifier == *identifier).unwrap().value;
-                            }
-                        } else {
-                            value = registers.iter().find(|x| x.identifier == *identifier).unwrap().value;
-                        }
-                        print!("{}", value);
-                    }
-                }
-                "mod" => {
-                    if let ExpressionType::ProbableLiteral(ref t_for) = self.arguments[0] {
-                        if let ExpressionType::ProbableLiteral(ref by) = self.arguments[1] {
-                            if let ExpressionType::StringLiteral(ref identifier) = self.arguments[2] {
-                                let t_for_val = self.get_absolute_value(file.unsafe_clone(), rh_err, t_for, registers, scope);
-                                if t_for_val.is_err() {
-                                    return Err(t_for_val.err().unwrap());
-                                }
-                                let by_val = self.get_absolute_value(file.unsafe_clone(), rh_err, by, registers, scope);
-                                if by_val.is_err() {
-                                    return Err(by_val.err().unwrap());
-                                }
-                                if self.g_register_exists(identifier.clone(), registers) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                } else if self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                }
-
-                                scope.push(Register {
-                                    identifier: identifier.clone(),
-                                    value: (t_for_val.ok().unwrap() % by_val.ok().unwrap()) as f64,
-                                    is_variable: true
-                                })
-                            }
-                        }
-                    }
-                }
-                "eq" => {
-                    if let ExpressionType::ProbableLiteral(ref t_for) = self.arguments[0] {
-                        if let ExpressionType::ProbableLiteral(ref by) = self.arguments[1] {
-                            if let ExpressionType::StringLiteral(ref identifier) = self.arguments[2] {
-                                let t_for_val = self.get_absolute_value(file.unsafe_clone(), rh_err, t_for, registers, scope);
-                                if t_for_val.is_err() {
-                                    return Err(t_for_val.err().unwrap());
-                                }
-                                let by_val = self.get_absolute_value(file.unsafe_clone(), rh_err, by, registers, scope);
-                                if by_val.is_err() {
-                                    return Err(by_val.err().unwrap());
-                                }
-
-                                if self.g_register_exists(identifier.clone(), registers) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                } else if self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                }
-                                scope.push(Register {
-                                    identifier: identifier.clone(),
-                                    value: if t_for_val.ok().unwrap() == by_val.ok().unwrap() { TRUE } else { FALSE },
-                                    is_variable: true
-                                })
-                            }
-                        }
-                    }
-                }
-                "lt" => {
-                    if let ExpressionType::ProbableLiteral(ref t_for) = self.arguments[0] {
-                        if let ExpressionType::ProbableLiteral(ref by) = self.arguments[1] {
-                            if let ExpressionType::StringLiteral(ref identifier) = self.arguments[2] {
-                                let t_for_val = self.get_absolute_value(file.unsafe_clone(), rh_err, t_for, registers, scope);
-                                if t_for_val.is_err() {
-                                    return Err(t_for_val.err().unwrap());
-
-                                }
-                                let by_val = self.get_absolute_value(file.unsafe_clone(), rh_err, by, registers, scope);
-                                if by_val.is_err() {
-                                    return Err(by_val.err().unwrap());
-                                }
-
-                                if self.g_register_exists(identifier.clone(), registers) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                } else if self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                }
-
-                                scope.push(Register {
-                                    identifier: identifier.clone(),
-                                    value: if t_for_val.ok().unwrap() < by_val.ok().unwrap() { TRUE } else { FALSE },
-                                    is_variable: true
-                                })
-                            }
-                        }
-                    }
-                }
-                "gt" => {
-                    if let ExpressionType::ProbableLiteral(ref t_for) = self.arguments[0] {
-                        if let ExpressionType::ProbableLiteral(ref by) = self.arguments[1] {
-                            if let ExpressionType::StringLiteral(ref identifier) = self.arguments[2] {
-                                let t_for_val = self.get_absolute_value(file.unsafe_clone(), rh_err, t_for, registers, scope);
-                                if t_for_val.is_err() {
-                                    return Err(t_for_val.err().unwrap());
-                                }
-                                let by_val = self.get_absolute_value(file.unsafe_clone(), rh_err, by, registers, scope);
-                                if by_val.is_err() {
-                                    return Err(by_val.err().unwrap());
-                                }
-
-                                if self.g_register_exists(identifier.clone(), registers) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                } else if self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                }
-
-                                scope.push(Register {
-                                    identifier: identifier.clone(),
-                                    value: if t_for_val.ok().unwrap() > by_val.ok().unwrap() { TRUE } else { FALSE },
-                                    is_variable: true
-                                })
-                            }
-                        }
-                    }
-                }
-                "gte" => {
-                    if let ExpressionType::ProbableLiteral(ref t_for) = self.arguments[0] {
-                        if let ExpressionType::ProbableLiteral(ref by) = self.arguments[1] {
-                            if let ExpressionType::StringLiteral(ref identifier) = self.arguments[2] {
-                                let t_for_val = self.get_absolute_value(file.unsafe_clone(), rh_err, t_for, registers, scope);
-                                if t_for_val.is_err() {
-                                    return Err(t_for_val.err().unwrap());
-                                }
-                                let by_val = self.get_absolute_value(file.unsafe_clone(), rh_err, by, registers, scope);
-                                if by_val.is_err() {
-                                    return Err(by_val.err().unwrap());
-                                }
-
-                                if self.g_register_exists(identifier.clone(), registers) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                } else if self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                }
-
-                                scope.push(Register {
-                                    identifier: identifier.clone(),
-                                    value: if t_for_val.ok().unwrap() >= by_val.ok().unwrap() { TRUE } else { FALSE },
-                                    is_variable: true
-                                })
-                            }
-                        }
-                    }
-                }
-                "lte" => {
-                    if let ExpressionType::ProbableLiteral(ref t_for) = self.arguments[0] {
-                        if let ExpressionType::ProbableLiteral(ref by) = self.arguments[1] {
-                            if let ExpressionType::StringLiteral(ref identifier) = self.arguments[2] {
-                                let t_for_val = self.get_absolute_value(file.unsafe_clone(), rh_err, t_for, registers, scope);
-                                if t_for_val.is_err() {
-                                    return Err(t_for_val.err().unwrap());
-                                }
-                                let by_val = self.get_absolute_value(file.unsafe_clone(), rh_err, by, registers, scope);
-                                if by_val.is_err() {
-                                    return Err(by_val.err().unwrap());
-                                }
-
-                                if self.g_register_exists(identifier.clone(), registers) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                } else if self.l_register_exists(identifier.clone(), scope) {
-                                    let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                                }
-
-                                scope.push(Register {
-                                    identifier: identifier.clone(),
-                                    value: if t_for_val.ok().unwrap() <= by_val.ok().unwrap() { TRUE } else { FALSE },
-                                    is_variable: true
-                                })
-                            }
-                        }
-                    }
-                }
-                "del" => {
-                    if let ExpressionType::VariableReference(ref name) = self.arguments[0] {
-                        // remove second lookup...
-                        let clone = scope.clone();
-                        let reg = clone.iter().find(|x| x.identifier == *name);
-                        if reg.is_some() {
-                            let x_name = reg.unwrap().identifier.clone();
-                            let pos = scope.iter().position(|x| x.identifier == x_name).unwrap();
-                            scope.remove(pos);
-                        } else {
-                            let err = IllError::NonExistentRegister(rh_err, name.clone());
-                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                        }
-                    }
-                }
-                "pt" => {
-                    if let ExpressionType::StringLiteral(ref s) = self.arguments[0] {
-                        print!("{}", s);
-                    }
-                }
-                "ptl" => {
-                    if let ExpressionType::StringLiteral(ref s) = self.arguments[0] {
-                        println!("{}", s);
-                    }
-                }
-                "for" => {
-                    if let ExpressionType::StringLiteral(ref injected_var_name) = self.arguments[0] {
-                        if let ExpressionType::IntegerLiteral(ref from) = self.arguments[1] {
-                            if let ExpressionType::IntegerLiteral(ref through) = self.arguments[2] {
-                                if let ExpressionType::IntegerLiteral(ref step) = self.arguments[3] {
-                                    if let ExpressionType::InstructionReference(ref inst, _) = self.arguments[4] {
-                                        if self.g_register_exists(injected_var_name.clone(), registers) {
-                                            let err = IllError::RegisterRedefinition(rh_err, injected_var_name.clone(), Some(register().name()));
-                                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                        } else if self.l_register_exists(injected_var_name.clone(), scope) {
-                                            let err = IllError::RegisterRedefinition(rh_err, injected_var_name.clone(), Some(variable().name()));
-                                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                        }
-                                        if !self.instruction_exists(inst, o_insts.clone()) {
-                                            let err = IllError::NonExistentInstruction(rh_err, inst.clone());
-                                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                        }
-                                        let start = (*from) - 1f64;
-                                        let mut clone = o_insts.clone();
-                                        scope.push(Register {
-                                            identifier: injected_var_name.clone(),
-                                            value: start,
-                                            is_variable: true,
-                                        });
-                                        let func = clone.iter_mut().find(|x| x.name == *inst).unwrap();
-                                        let mut val = start;
-                                        while if val > *through { val > *through } else { val < *through } {
-                                            let res = func.c_execute(file.unsafe_clone(), debug, registers, o_insts.clone(), scope);
-                                            if res.is_err() {
-                                                return Err(res.err().unwrap());
-                                            }
-                                            val = scope.iter().find(|x| x.identifier == *injected_var_name).unwrap().value;
-                                            if from > through {
-                                                val -= *step;
-                                            } else {
-                                                val += *step;
-                                            }
-                                            scope.iter_mut().find(|x| x.identifier == *injected_var_name).unwrap().value = val;
-                                        }
-                                    }
-                                    let pos = scope.iter().position(|x| x.identifier == *injected_var_name).unwrap();
-                                    scope.remove(pos);
-                                }
-                            }
-                        }
-                    }
-                }
-                "if" => {
-                    if let ExpressionType::InstructionReference(ref inst, _) = self.arguments[0] {
-                        if let ExpressionType::InstructionReference(ref a_inst, _) = self.arguments[1] {
-                            if let ExpressionType::InstructionReference(ref b_inst, _) = self.arguments[2] {
-                                let nested_clone = o_insts.clone();
-                                if !self.instruction_exists(inst, nested_clone) {
-                                    let err = IllError::NonExistentInstruction(rh_err, inst.clone());
-                                    return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                }
-                                let result = get_and_execute(file.unsafe_clone(), inst, debug, registers, o_insts.clone(), scope);
-                                if result.is_err() {
-                                    return Err(result.err().unwrap());
-                                } else {
-                                    let unr = result.ok().unwrap();
-                                    if !self.instruction_exists(a_inst, o_insts.clone()) {
-                                        let err = IllError::NonExistentInstruction(rh_err, a_inst.clone());
-                                        return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                    } else if !self.instruction_exists(b_inst, o_insts.clone()) {
-                                        let err = IllError::NonExistentInstruction(rh_err, b_inst.clone());
-                                        return Err(AdvancedIllError::new(err, rh_err_o, file));
-                                    }
-                                    if unr == TRUE {
-                                        let res = o_insts.clone().iter_mut().find(|x| x.name == *a_inst).unwrap().c_execute(file.unsafe_clone(), debug, registers, o_insts.clone(), scope);
-                                        if res.is_err() {
-                                            return Err(res.err().unwrap());
-                                        }
-                                    } else {
-                                        let res = o_insts.clone().iter_mut().find(|x| x.name == *b_inst).unwrap().c_execute(file.unsafe_clone(), debug, registers, o_insts.clone(), scope);
-                                        if res.is_err() {
-                                            return Err(res.err().unwrap());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                "do" => {
-                    if let ExpressionType::InstructionReference(ref inst, _) = self.arguments[0] {
-                        if self.instruction_exists(inst, o_insts.clone()) {
-                            let copy = o_insts.clone();
-                            o_insts.iter_mut().find(|x| x.name == *inst).unwrap().c_execute(file.unsafe_clone(), debug, registers, copy, scope).ok().unwrap();
-                        } else {
-                            let err = IllError::NonExistentInstruction(rh_err, inst.clone());
-                            return Err(AdvancedIllError::new(err, rh_err_o, file));
-                        }
-                    }
-                }
-                "dor" => {
-                    if let ExpressionType::InstructionReference(ref inst, _) = self.arguments[0] {
-                        if let ExpressionType::StringLiteral(ref identifier) = self.arguments[1] {
-                            if self.g_register_exists(identifier.clone(), registers) {
-                                let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(register().name()));
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-                            } else if self.l_register_exists(identifier.clone(), scope) {
-                                let err = IllError::RegisterRedefinition(rh_err, identifier.clone(), Some(variable().name()));
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-                            }
-                            if self.instruction_exists(inst, o_insts.clone()) {
-                                let copy = o_insts.clone();
-                                let res = o_insts.iter_mut().find(|x| x.name == *inst).unwrap().c_execute(file.unsafe_clone(), debug, registers, copy, scope);
-                                if res.is_ok() {
-                                    scope.push(Register {
-                                        identifier: identifier.clone(),
-                                        value: res.ok().unwrap(),
-                                        is_variable: true,
-                                    });
-                                } else {
-                                    return Err(res.err().unwrap());
-                                }
-                            } else {
-                                let err = IllError::NonExistentInstruction(rh_err, inst.clone());
-                                return Err(AdvancedIllError::new(err, rh_err_o, file));
-
-                            }
-                        }
-                    }
+        pub fn head(&self) -> ReadHead {
+            self.location.unwrap_or_default()
+        }
+
+        pub fn execute(&self, ctx: &mut ExecutionContext) -> Result<Flow, AdvancedIllError> {
+            match ctx.opcodes.find_handler(&self.name) {
+                Some(handler) => handler.execute(self, ctx),
+                None => {
+                    let head = self.head();
+                    Err(ctx.error(IllError::UnknownOpCode(head, self.name.clone()), head))
                 }
-                _ => ()
             }
-            Ok(())
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,322 @@
+pub mod ill {
+    use ast::ill::{Argument, ArgumentValue, Code, Expression, InstructionDecl, Program, RegisterDecl, Use};
+    use interpreter::ill::{AdvancedIllError, EnhancedFile, IllError, ReadHead};
+    use lexer::ill::{tokenize, Span, Token, TokenKind, Tokens};
+
+    const REGISTER_DEF: char = '+';
+    pub const DEF_END: char = ';';
+    const CONSTANT_MARK: char = '!';
+    // makes a module's register or instruction usable from outside it
+    const EXPORT_MARK: char = '^';
+    const REGISTER_INIT: char = '=';
+
+    // instructions
+    pub const INST_DEF: char = '$';
+
+    const INST_PARAM_BEGIN: char = '(';
+    const INST_PARAM_END: char = ')';
+
+    const INST_CODES_BEGIN: char = '{';
+    const INST_CODES_END: char = '}';
+
+    // modules
+    const USE_DIRECTIVE: &str = "use";
+
+    /// `use std::math;` loads a module of the bundled standard library.
+    pub const STD_PREFIX: &str = "std::";
+
+    /// Reads a whole file into its syntax tree. Nothing is looked up, so this works on any file on its own.
+    ///
+    /// A mistake doesn't end the parse: the parser skips to the next `;` or `}` and carries on, so the tree holds
+    /// everything that could be read and the errors list every mistake in the file, in order.
+    pub fn parse(file: &EnhancedFile) -> (Program, Vec<AdvancedIllError>) {
+        let (tokens, mut errors) = tokenize(file);
+        let mut parser = Parser { tokens: Tokens::new(file, tokens), file, errors: Vec::new() };
+        let mut program = Program { file: file.clone(), ..Default::default() };
+        while let Some(token) = parser.tokens.peek().cloned() {
+            let result = if token.word() == Some(USE_DIRECTIVE) {
+                parser.use_directive().map(|x| program.uses.push(x))
+            } else if token.is_symbol(REGISTER_DEF) {
+                parser.registers(&mut program.registers);
+                Ok(())
+            } else if token.is_symbol(INST_DEF) {
+                parser.instruction().map(|x| program.instructions.push(x))
+            } else {
+                Err(parser.tokens.unexpected("`use`, a `+` line of registers or a `$` instruction"))
+            };
+            if let Err(err) = result {
+                parser.errors.push(err);
+                parser.skip_item();
+            }
+        }
+        let position = |err: &AdvancedIllError| err.head.map(|head| (head.line, head.column));
+        // a String that's never closed takes the rest of the file with it, so anything the parser found after it is noise
+        if let Some(unclosed) = errors.iter().find(|x| matches!(*x.error, IllError::UnterminatedString(_))).map(position) {
+            parser.errors.retain(|x| position(x) <= unclosed);
+        }
+        errors.extend(parser.errors);
+        errors.sort_by_key(position);
+        (program, errors)
+    }
+
+    struct Parser<'a> {
+        tokens: Tokens<'a>,
+        file: &'a EnhancedFile,
+        errors: Vec<AdvancedIllError>,
+    }
+
+    impl<'a> Parser<'a> {
+        fn error(&self, err: IllError, head: ReadHead) -> AdvancedIllError {
+            AdvancedIllError::new(err, Some(head), self.file.clone())
+        }
+
+        fn peek_is(&self, ch: char) -> bool {
+            self.tokens.peek().is_some_and(|x| x.is_symbol(ch))
+        }
+
+        fn starts_item(token: &Token) -> bool {
+            token.is_symbol(INST_DEF) || token.is_symbol(REGISTER_DEF) || token.word() == Some(USE_DIRECTIVE)
+        }
+
+        // past a broken top-level item: to the end of its `;` or `{ }` block, or up to where the next item starts
+        fn skip_item(&mut self) {
+            let mut depth = 0usize;
+            while let Some(token) = self.tokens.peek().cloned() {
+                if depth == 0 && Parser::starts_item(&token) {
+                    return;
+                }
+                self.tokens.next();
+                if token.is_symbol(INST_CODES_BEGIN) {
+                    depth += 1;
+                } else if token.is_symbol(INST_CODES_END) {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return;
+                    }
+                } else if depth == 0 && token.is_symbol(DEF_END) {
+                    return;
+                }
+            }
+        }
+
+        // past a broken OpCode: through its `;`, or up to the `}` or `$` that ends the instruction
+        fn skip_code(&mut self) {
+            while let Some(token) = self.tokens.peek() {
+                if token.is_symbol(INST_CODES_END) || token.is_symbol(INST_DEF) {
+                    return;
+                }
+                if self.tokens.next().unwrap().is_symbol(DEF_END) {
+                    return;
+                }
+            }
+        }
+
+        // the next token, if it sits right against `span`
+        fn touching(&self, span: &Span) -> Option<&Token> {
+            self.tokens.peek().filter(|x| span.touches(&x.span))
+        }
+
+        fn use_directive(&mut self) -> Result<Use, AdvancedIllError> {
+            let start = self.tokens.next().unwrap().span;
+            let path = match self.tokens.peek().map(|x| x.kind.clone()) {
+                Some(TokenKind::Str(path)) => path,
+                Some(TokenKind::Word(ref name)) if name.starts_with(STD_PREFIX) => name.clone(),
+                _ => return Err(self.tokens.unexpected("a quoted path or `std::name` after `use`")),
+            };
+            let end = self.tokens.next().unwrap().span;
+            self.tokens.expect(DEF_END, "`;` after the used module")?;
+            Ok(Use { path, span: start.to(&end) })
+        }
+
+        // a `+` and the definitions after it, each ended by `;`, up to the end of the line; a broken definition is
+        // skipped up to its `;`
+        fn registers(&mut self, registers: &mut Vec<RegisterDecl>) {
+            let mut line = self.tokens.next().unwrap().span.line;
+            while self.tokens.peek().is_some_and(|x| x.span.line == line) {
+                match self.register() {
+                    Ok((decl, end)) => {
+                        registers.push(decl);
+                        line = end;
+                    }
+                    Err(err) => {
+                        self.errors.push(err);
+                        while let Some(token) = self.tokens.peek().filter(|x| x.span.line == line).cloned() {
+                            self.tokens.next();
+                            if token.is_symbol(DEF_END) {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // one definition and the line of the `;` that ends it
+        fn register(&mut self) -> Result<(RegisterDecl, i32), AdvancedIllError> {
+            let (mut is_constant, mut is_exported) = (false, false);
+            loop {
+                if self.tokens.eat(CONSTANT_MARK).is_some() {
+                    is_constant = true;
+                } else if self.tokens.eat(EXPORT_MARK).is_some() {
+                    is_exported = true;
+                } else {
+                    break;
+                }
+            }
+            let (name, span) = self.tokens.expect_word("a register name")?;
+            let initializer = match self.tokens.eat(REGISTER_INIT) {
+                Some(_) => Some(self.sum()?),
+                None => None,
+            };
+            let end = self.tokens.expect(DEF_END, "`;` to end the register definition")?.span.line;
+            Ok((RegisterDecl { name, span, is_constant, is_exported, initializer }, end))
+        }
+
+        // the operator at the front, if it's one of `ops`
+        fn operator(&mut self, ops: &str) -> Option<(char, Span)> {
+            match self.tokens.peek().map(|x| (&x.kind, x.span)) {
+                Some((&TokenKind::Symbol(op), span)) if ops.contains(op) => {
+                    self.tokens.next();
+                    Some((op, span))
+                }
+                _ => None,
+            }
+        }
+
+        fn sum(&mut self) -> Result<Expression, AdvancedIllError> {
+            let mut expr = self.product()?;
+            while let Some((op, span)) = self.operator("+-") {
+                expr = Expression::Binary(op, Box::new(expr), Box::new(self.product()?), span);
+            }
+            Ok(expr)
+        }
+
+        fn product(&mut self) -> Result<Expression, AdvancedIllError> {
+            let mut expr = self.unary()?;
+            while let Some((op, span)) = self.operator("*/%") {
+                expr = Expression::Binary(op, Box::new(expr), Box::new(self.unary()?), span);
+            }
+            Ok(expr)
+        }
+
+        fn unary(&mut self) -> Result<Expression, AdvancedIllError> {
+            match self.operator("-") {
+                Some((_, span)) => Ok(Expression::Negate(Box::new(self.unary()?), span)),
+                None => self.primary(),
+            }
+        }
+
+        fn primary(&mut self) -> Result<Expression, AdvancedIllError> {
+            let token = match self.tokens.peek().cloned() {
+                Some(ref token) if !token.is_symbol(DEF_END) => token.clone(),
+                _ => return Err(self.tokens.unexpected("a value")),
+            };
+            self.tokens.next();
+            match token.kind {
+                TokenKind::Symbol(INST_PARAM_BEGIN) => {
+                    let expr = self.sum()?;
+                    if !self.peek_is(INST_PARAM_END) {
+                        return Err(self.tokens.unexpected("`)`"));
+                    }
+                    self.tokens.next();
+                    Ok(expr)
+                }
+                TokenKind::Number(n) => Ok(Expression::Number(n, token.span)),
+                TokenKind::Str(text) => Ok(Expression::Str(text, token.span)),
+                TokenKind::Word(name) => Ok(Expression::Name(name, token.span)),
+                TokenKind::Symbol(_) => {
+                    let head = token.span.head();
+                    Err(self.error(IllError::UnexpectedToken(head, token.to_string(), String::from("a value")), head))
+                }
+            }
+        }
+
+        fn instruction(&mut self) -> Result<InstructionDecl, AdvancedIllError> {
+            let start = self.tokens.next().unwrap().span;
+            let is_main = self.tokens.eat(INST_DEF).is_some();
+            let is_exported = self.tokens.eat(EXPORT_MARK).is_some();
+            let (name, name_span) = self.tokens.expect_word("an instruction name")?;
+            self.tokens.expect(INST_PARAM_BEGIN, "`(` before the instruction's parameters")?;
+            let mut parameters = Vec::new();
+            while self.tokens.peek().is_some_and(|x| x.word().is_some()) {
+                parameters.push(self.tokens.expect_word("a parameter name")?.0);
+            }
+            self.tokens.expect(INST_PARAM_END, "a parameter name or `)`")?;
+            self.tokens.expect(INST_CODES_BEGIN, "`{` to begin the instruction's codes")?;
+            let mut codes = Vec::new();
+            while self.tokens.eat(INST_CODES_END).is_none() {
+                // the next instruction or the end of the file, so this one was never closed
+                if self.tokens.peek().is_none_or(|x| x.is_symbol(INST_DEF)) {
+                    self.errors.push(self.tokens.unexpected("`}` to end the instruction"));
+                    break;
+                }
+                // a stray `;` is an empty code
+                if self.tokens.eat(DEF_END).is_none() {
+                    match self.code() {
+                        Ok(code) => codes.push(code),
+                        Err(err) => {
+                            self.errors.push(err);
+                            self.skip_code();
+                            // the code ran into the end of the instruction, which says all there is to say
+                            if self.tokens.peek().is_none_or(|x| x.is_symbol(INST_DEF)) {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(InstructionDecl { name, span: start.to(&name_span), is_main, is_exported, parameters, codes })
+        }
+
+        // `name arguments...;`
+        fn code(&mut self) -> Result<Code, AdvancedIllError> {
+            let (name, span) = self.tokens.expect_word("an OpCode")?;
+            let mut arguments = Vec::new();
+            while !self.peek_is(DEF_END) && !self.peek_is(INST_CODES_END) && self.tokens.peek().is_some() {
+                arguments.push(self.argument()?);
+            }
+            self.tokens.expect(DEF_END, "`;` to end the OpCode")?;
+            Ok(Code { name, span, arguments })
+        }
+
+        fn argument(&mut self) -> Result<Argument, AdvancedIllError> {
+            let token = self.tokens.next().unwrap();
+            let value = match token.kind {
+                TokenKind::Symbol('-') => match self.touching(&token.span).map(|x| (x.kind.clone(), x.span)) {
+                    Some((TokenKind::Number(n), span)) => {
+                        self.tokens.next();
+                        return Ok(Argument { value: ArgumentValue::Number(-n), span: token.span.to(&span) });
+                    }
+                    _ => None,
+                },
+                TokenKind::Word(ref name) if self.touching(&token.span).is_some_and(|x| x.is_symbol(INST_PARAM_BEGIN)) => {
+                    let open = self.tokens.next().unwrap().span;
+                    let mut arguments = Vec::new();
+                    loop {
+                        match self.tokens.peek() {
+                            Some(x) if x.is_symbol(INST_PARAM_END) => break,
+                            Some(x) if !x.is_symbol(DEF_END) && !x.is_symbol(INST_CODES_END) => arguments.push(self.argument()?),
+                            _ => {
+                                let err = IllError::UnexpectedToken(open.head(), String::from("the end of the OpCode"), format!("a `{}` to close this `{}`", INST_PARAM_END, INST_PARAM_BEGIN));
+                                return Err(self.error(err, open.head()));
+                            }
+                        }
+                    }
+                    let close = self.tokens.next().unwrap().span;
+                    return Ok(Argument { value: ArgumentValue::Call(name.clone(), arguments), span: token.span.to(&close) });
+                }
+                TokenKind::Word(ref name) => Some(ArgumentValue::Name(name.clone())),
+                TokenKind::Number(n) => Some(ArgumentValue::Number(n)),
+                TokenKind::Str(ref text) => Some(ArgumentValue::Str(text.clone())),
+                TokenKind::Symbol(_) => None,
+            };
+            match value {
+                Some(value) => Ok(Argument { value, span: token.span }),
+                None => {
+                    let head = token.span.head();
+                    Err(self.error(IllError::UnexpectedToken(head, token.to_string(), String::from("an argument")), head))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+pub mod ill {
+    /// The modules of the standard library, by the name they're used with (`use std::math;`).
+    pub const MODULES: &[(&str, &str)] = &[
+        ("math", include_str!("../std/math.ill")),
+        ("logic", include_str!("../std/logic.ill")),
+        ("io", include_str!("../std/io.ill")),
+    ];
+
+    pub fn find(name: &str) -> Option<&'static str> {
+        MODULES.iter().find(|&&(x, _)| x == name).map(|&(_, content)| content)
+    }
+}
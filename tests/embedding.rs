@@ -0,0 +1,210 @@
+extern crate pill;
+
+use std::io::Cursor;
+use std::thread;
+
+use pill::interpreter::ill::{IllError, Interpreter};
+use pill::opcodes::ill::default_opcodes;
+
+#[test]
+fn runs_on_another_thread() {
+    let interpreter = Interpreter::new(default_opcodes())
+        .input_from(Cursor::new("7\n"))
+        .source("test.ill", "+n;\n$$main() { rdn n; dsl n; }");
+    let result = thread::spawn(move || interpreter.run()).join().unwrap();
+    assert!(result.is_ok());
+    assert_eq!(result.output, "7\n");
+}
+
+fn first_error(program: &str) -> IllError {
+    let result = Interpreter::new(default_opcodes()).source("test.ill", program).run();
+    *result.errors.into_iter().next().expect("the program should have failed").error
+}
+
+#[test]
+fn depth_limit_comes_before_a_stack_overflow() {
+    // every call goes through another handler on its way back to `f`
+    let program = "+n;
+$f() { for i 0 1 1 g(); }
+$g() { lst q; psh 1 q; each j q h(); }
+$h() { if c() f() f(); }
+$c() { mov 0 res; }
+$$main() { do f(); }";
+    match first_error(program) {
+        IllError::MaximumRecursionDepth(_, _, 1000) => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn res_cannot_be_deleted() {
+    match first_error("+n;\n$$main() { del res; }") {
+        IllError::ImmutableRegister(_, ref name) if name == "res" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+fn output(program: &str) -> String {
+    let result = Interpreter::new(default_opcodes()).source("test.ill", program).run();
+    if let Some(err) = result.errors.first() {
+        panic!("{}", err.error);
+    }
+    result.output
+}
+
+#[test]
+fn ret_leaves_through_an_if() {
+    let program = "+n;
+$small(k) { lte k 1 t; mov t res; }
+$one() { ret 1; }
+$more(k) { mak m k; sub 1 m; dor fact(m) r; mul k r; ret r; }
+$fact(k) { if small(k) one() more(k); mov 0 res; }
+$$main() { dor fact(5) f; dsl f; }";
+    assert_eq!(output(program), "120\n");
+}
+
+#[test]
+fn loop_control_outside_a_loop() {
+    match first_error("+n;\n$helper() { brk; }\n$$main() { do helper(); ptl \"x\"; }") {
+        IllError::LoopControlOutsideLoop(_, ref name) if name == "brk" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+    assert_eq!(output("+n;\n$helper() { brk; }\n$body(i) { dsl i; do helper(); }\n$$main() { for i 0 3 1 body(i); }"), "0\n");
+}
+
+#[test]
+fn syntax_errors_hide_nothing_else() {
+    let program = "+a;b;a;
+$f() {
+    foo 1;
+    mov;
+    dsl a
+}
+$$main() { do f(); }";
+    let result = Interpreter::new(default_opcodes()).source("test.ill", program).run();
+    let names: Vec<String> = result.errors.iter().map(|x| x.error.name()).collect();
+    assert_eq!(names, vec!["Unexpected Token", "Register Redefinition", "Unknown OpCode", "OpCode Argument Length Mismatch"]);
+}
+
+#[test]
+fn loop_step_too_small_to_count() {
+    match first_error("+n;\n$f(i) { dsl i; }\n$$main() { for i 1e20 1e21 1 f(i); }") {
+        IllError::InvalidLoopStep(_, 1.0) => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn substring_bounds_are_whole_and_shown_as_given() {
+    match first_error("+n;\n$$main() { sbs \"abc\" 0.5 2 s; }") {
+        IllError::IndexOutOfBounds(_, 0.5, 3) => {}
+        other => panic!("unexpected error: {}", other),
+    }
+    let err = first_error("+n;\n$$main() { sbs \"abc\" 0 1e10 s; }");
+    assert!(err.to_string().contains("Index 10000000000 is out of bounds for a length of 3."), "{}", err);
+    assert_eq!(output("+n;\n$$main() { sbs \"abc\" 1 3 s; dsl s; }"), "bc\n");
+}
+
+#[test]
+fn constant_without_a_value() {
+    match first_error("+!PI;n;\n$$main() { dsl n; }") {
+        IllError::ConstantWithoutValue(_, ref name) if name == "PI" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn constants_cannot_be_deleted() {
+    let result = Interpreter::new(default_opcodes()).source("test.ill", "+!PI=3;n;\n$$main() { ptl \"x\"; del PI; }").run();
+    assert_eq!(result.output, "");
+    match *result.errors.into_iter().next().expect("the program should have failed").error {
+        IllError::ImmutableRegister(_, ref name) if name == "PI" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn fractional_steps_keep_their_bounds() {
+    // ten steps of 0.1 stop short of 1, the last one at 0.9 rather than 0.9999999999999999
+    let program = "+c;last;\n$f(i) { add 1 c; mov i last; }\n$$main() { for i 0 1 0.1 f(i); dsl c; dsl last; }";
+    assert_eq!(output(program), "10\n0.9\n");
+    assert_eq!(output("+n;\n$f(i) { dsl i; }\n$$main() { fore i 0 1 0.25 f(i); }"), "0\n0.25\n0.5\n0.75\n1\n");
+}
+
+#[test]
+fn depth_limit_too_large_for_a_stack() {
+    let result = Interpreter::new(default_opcodes())
+        .max_call_depth(100_000_000_000)
+        .source("test.ill", "+n;\n$$main() { dsl n; }")
+        .run();
+    assert_eq!(result.exit_code, 2);
+    match *result.errors.into_iter().next().expect("the program should have failed").error {
+        IllError::CallDepthTooLarge(100_000_000_000) => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn streamed_output_is_not_kept() {
+    let result = Interpreter::new(default_opcodes())
+        .stream_output(true)
+        .source("test.ill", "+n;\n$$main() { ptl \"streamed\\n\"; }")
+        .run();
+    assert!(result.is_ok());
+    assert_eq!(result.output, "");
+}
+
+#[test]
+fn exit_codes_are_whole_and_in_range() {
+    for code in &["3.7", "256", "-1", "1e10"] {
+        match first_error(&format!("+n;\n$$main() {{ exit {}; }}", code)) {
+            IllError::InvalidExitCode(_, _) => {}
+            other => panic!("unexpected error for {}: {}", code, other),
+        }
+    }
+    let result = Interpreter::new(default_opcodes()).source("test.ill", "+n;\n$$main() { exit 255; }").run();
+    assert_eq!(result.exit_code, 255);
+}
+
+fn exit_code(program: &str) -> i32 {
+    Interpreter::new(default_opcodes()).source("test.ill", program).run().exit_code
+}
+
+#[test]
+fn exit_code_for_each_outcome() {
+    assert_eq!(exit_code("+n;\n$$main() { dsl n; }"), 0);
+    // a runtime error, then one found before anything ran
+    assert_eq!(exit_code("+n;\n$$main() { div 0 n; }"), 1);
+    assert_eq!(exit_code("+n;\n$$main() { bogus; }"), 2);
+}
+
+#[test]
+fn exit_from_deep_inside() {
+    let program = "+n;
+$leave() { exit 7; }
+$pick(i) { if late(i) leave() stay(); }
+$late(i) { gte i 2 t; mov t res; }
+$stay() { mov 0 res; }
+$body(i) { dsl i; dor pick(i) r; }
+$$main() { for i 0 5 1 body(i); ptl \"not reached\"; }";
+    let result = Interpreter::new(default_opcodes()).source("test.ill", program).run();
+    assert!(result.is_ok());
+    assert_eq!(result.output, "0\n1\n2\n");
+    assert_eq!(result.exit_code, 7);
+}
+
+#[test]
+fn calls_reach_instructions_declared_anywhere() {
+    // `main` calls `later` before it's declared
+    assert_eq!(output("+n;\n$$main() { do later(); }\n$later() { ptl \"later\"; }"), "later\n");
+
+    // `ping` lives in the preamble and `pong` in the source, and each calls the other
+    let preamble = "$ping(k) { dsl k; if done(k) stop() pong(k); }\n$stop() { mov 0 res; }\n$done(k) { lte k 0 t; mov t res; }";
+    let source = "+n;\n$pong(k) { mak m k; sub 1 m; do ping(m); }\n$$main() { do ping(3); }";
+    let result = Interpreter::new(default_opcodes())
+        .preamble("pre.ill", preamble)
+        .source("test.ill", source)
+        .run();
+    assert!(result.is_ok(), "{:?}", result.errors.iter().map(|x| x.error.to_string()).collect::<Vec<_>>());
+    assert_eq!(result.output, "3\n2\n1\n0\n");
+}
@@ -0,0 +1,88 @@
+extern crate pill;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pill::interpreter::ill::{IllError, Interpreter};
+use pill::opcodes::ill::default_opcodes;
+
+// a fresh directory for one test, holding an allowed `data` directory and an `outside` one next to it
+fn workspace(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("pill-files-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data")).unwrap();
+    fs::create_dir_all(dir.join("outside")).unwrap();
+    dir
+}
+
+// writes "hi" to `path`, with only `data` allowed
+fn write(dir: &Path, path: &Path) -> Result<(), IllError> {
+    let program = format!("+n;\n$$main() {{ opn \"{}\" \"w\" f; wrt \"hi\" f; cls f; }}", path.display());
+    let result = Interpreter::new(default_opcodes())
+        .allow_dir(dir.join("data"))
+        .source("test.ill", &program)
+        .run();
+    match result.errors.into_iter().next() {
+        Some(err) => Err(*err.error),
+        None => Ok(()),
+    }
+}
+
+fn assert_not_allowed(result: Result<(), IllError>) {
+    match result {
+        Err(IllError::PathNotAllowed(_, _)) => {}
+        Err(other) => panic!("unexpected error: {}", other),
+        Ok(()) => panic!("the file should not have been opened"),
+    }
+}
+
+#[test]
+fn allowed_path() {
+    let dir = workspace("allowed");
+    write(&dir, &dir.join("data").join("out.txt")).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("data").join("out.txt")).unwrap(), "hi");
+}
+
+#[test]
+fn denied_path() {
+    let dir = workspace("denied");
+    assert_not_allowed(write(&dir, &dir.join("outside").join("out.txt")));
+    assert!(!dir.join("outside").join("out.txt").exists());
+}
+
+#[test]
+fn parent_directory_escape() {
+    let dir = workspace("parent");
+    assert_not_allowed(write(&dir, &dir.join("data").join("..").join("outside").join("out.txt")));
+    assert!(!dir.join("outside").join("out.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_escape() {
+    use std::os::unix::fs::symlink;
+
+    let dir = workspace("symlink");
+    // one link to a file that's already there, and one to a file that isn't yet
+    fs::write(dir.join("outside").join("there.txt"), "").unwrap();
+    symlink(dir.join("outside").join("there.txt"), dir.join("data").join("there.txt")).unwrap();
+    symlink(dir.join("outside").join("new.txt"), dir.join("data").join("new.txt")).unwrap();
+    assert_not_allowed(write(&dir, &dir.join("data").join("there.txt")));
+    assert_not_allowed(write(&dir, &dir.join("data").join("new.txt")));
+    assert_eq!(fs::read_to_string(dir.join("outside").join("there.txt")).unwrap(), "");
+    assert!(!dir.join("outside").join("new.txt").exists());
+}
+
+#[test]
+fn missing_directory() {
+    let dir = workspace("missing");
+    // inside `data` it's an ordinary failure, but outside it's still nobody's business what's missing
+    match write(&dir, &dir.join("data").join("nope").join("out.txt")) {
+        Err(IllError::FileError(_, _)) => {}
+        Err(other) => panic!("unexpected error: {}", other),
+        Ok(()) => panic!("the file should not have been opened"),
+    }
+    assert_not_allowed(write(&dir, &dir.join("outside").join("nope").join("out.txt")));
+    assert_not_allowed(write(&dir, &dir.join("data").join("..").join("outside").join("nope").join("out.txt")));
+}
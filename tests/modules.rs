@@ -0,0 +1,101 @@
+extern crate pill;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pill::interpreter::ill::{ExecutionResult, IllError, Interpreter};
+use pill::opcodes::ill::default_opcodes;
+
+// a fresh directory for one test, with `files` written into it
+fn workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("pill-modules-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for &(path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+// runs `program` as if it were `main.ill` in `dir`
+fn run(dir: &Path, program: &str) -> ExecutionResult {
+    Interpreter::new(default_opcodes())
+        .source(&dir.join("main.ill").to_string_lossy(), program)
+        .run()
+}
+
+fn first_error(result: ExecutionResult) -> IllError {
+    *result.errors.into_iter().next().expect("the program should have failed").error
+}
+
+#[test]
+fn initializers_respect_privacy() {
+    let dir = workspace("initializers", &[("lib.ill", "+secret=42;^!SHARED=7;")]);
+    let result = run(&dir, "use \"lib.ill\";\n+n=lib::SHARED;\n$$main() { dsl n; }");
+    assert_eq!(result.output, "7\n");
+    match first_error(run(&dir, "use \"lib.ill\";\n+n=lib::secret;\n$$main() { dsl n; }")) {
+        IllError::PrivateItem(_, ref name) if name == "lib::secret" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn used_relative_to_the_importer() {
+    // `outer.ill` finds `inner.ill` next to itself, not next to `main.ill`
+    let dir = workspace("relative", &[
+        ("lib/outer.ill", "use \"inner.ill\";\n$^twice(x) { dor inner::double(x) y; ret y; }"),
+        ("lib/inner.ill", "$^double(x) { mov x res; mul 2 res; }"),
+    ]);
+    let result = run(&dir, "use \"lib/outer.ill\";\n+n;\n$$main() { dor outer::twice(21) x; dsl x; }");
+    assert!(result.is_ok());
+    assert_eq!(result.output, "42\n");
+}
+
+#[test]
+fn module_path() {
+    let dir = workspace("path", &[("shared/greet.ill", "$^hello() { ptl \"hello\"; }")]);
+    let program = "use \"greet.ill\";\n+n;\n$$main() { do greet::hello(); }";
+    match first_error(run(&dir, program)) {
+        IllError::ModuleNotFound(_, ref path) if path == "greet.ill" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+    let result = Interpreter::new(default_opcodes())
+        .module_path(dir.join("shared"))
+        .source(&dir.join("main.ill").to_string_lossy(), program)
+        .run();
+    assert!(result.is_ok());
+    assert_eq!(result.output, "hello\n");
+}
+
+#[test]
+fn private_instruction() {
+    let dir = workspace("private", &[("lib.ill", "$hidden() { ret 1; }\n$^shown() { dor hidden() x; ret x; }")]);
+    assert_eq!(run(&dir, "use \"lib.ill\";\n+n;\n$$main() { dor lib::shown() x; dsl x; }").output, "1\n");
+    match first_error(run(&dir, "use \"lib.ill\";\n+n;\n$$main() { dor lib::hidden() x; }")) {
+        IllError::PrivateItem(_, ref name) if name == "lib::hidden" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn two_files_with_one_name() {
+    let dir = workspace("redefinition", &[("a/util.ill", "$^f() { ret 1; }"), ("b/util.ill", "$^f() { ret 2; }")]);
+    match first_error(run(&dir, "use \"a/util.ill\";\nuse \"b/util.ill\";\n+n;\n$$main() { }")) {
+        IllError::ModuleRedefinition(_, ref name) if name == "util" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn cyclic_use() {
+    // each is loaded once, and each can call the other
+    let dir = workspace("cycle", &[
+        ("even.ill", "use \"odd.ill\";\n$zero() { ret 0; }\n$down(k) { mak m k; sub 1 m; dor odd::is(m) r; ret r; }\n$^is(k) { if none(k) zero() down(k); }\n$none(k) { eq k 0 t; mov t res; }"),
+        ("odd.ill", "use \"even.ill\";\n$one() { ret 1; }\n$down(k) { mak m k; sub 1 m; dor even::is(m) r; ret r; }\n$^is(k) { if none(k) one() down(k); }\n$none(k) { eq k 0 t; mov t res; }"),
+    ]);
+    let result = run(&dir, "use \"even.ill\";\n+n;\n$$main() { dor even::is(7) a; dsl a; dor even::is(4) b; dsl b; }");
+    assert!(result.is_ok(), "{:?}", result.errors.iter().map(|x| x.error.to_string()).collect::<Vec<_>>());
+    assert_eq!(result.output, "1\n0\n");
+}
@@ -0,0 +1,113 @@
+extern crate pill;
+
+use pill::interpreter::ill::{IllError, Interpreter};
+use pill::opcodes::ill::default_opcodes;
+
+fn run(program: &str) -> String {
+    let result = Interpreter::new(default_opcodes())
+        .std_library(true)
+        .source("test.ill", program)
+        .run();
+    if let Some(err) = result.errors.first() {
+        panic!("{}", err.error);
+    }
+    result.output
+}
+
+fn error(program: &str) -> IllError {
+    let result = Interpreter::new(default_opcodes()).source("test.ill", program).run();
+    *result.errors.into_iter().next().expect("the program should have failed").error
+}
+
+#[test]
+fn math() {
+    let output = run("+n;
+$$main() {
+    dor math::fact(0) a; dsl a;
+    dor math::fact(5) b; dsl b;
+    dor math::abs(-4) c; dsl c;
+    dor math::min(3 7) d; dsl d;
+    dor math::max(3 7) e; dsl e;
+    dor math::clamp(12 0 10) f; dsl f;
+    dor math::clamp(-2 0 10) g; dsl g;
+}");
+    assert_eq!(output, "1\n120\n4\n3\n7\n10\n0\n");
+}
+
+#[test]
+fn logic() {
+    let output = run("+n;
+$table(a b) {
+    dor logic::and(a b) x; dis x;
+    dor logic::or(a b) y; dis y;
+    dor logic::xor(a b) z; dsl z;
+}
+$$main() {
+    do table(logic::TRUE logic::TRUE);
+    do table(logic::TRUE logic::FALSE);
+    do table(logic::FALSE logic::FALSE);
+    dor logic::not(logic::TRUE) m; dsl m;
+    dor logic::truth(\"yes\") t; dsl t;
+}");
+    assert_eq!(output, "001\n100\n111\n1\n1\n");
+}
+
+#[test]
+fn helpers_ignore_the_programs_registers() {
+    // every name the helpers use for themselves, taken by the program
+    let output = run("+t;x;y;i;m;p;acc;
+$$main() {
+    dor logic::not(logic::FALSE) a; dsl a;
+    dor logic::or(logic::FALSE logic::TRUE) b; dsl b;
+    dor math::fact(5) c; dsl c;
+    dsl t;
+}");
+    assert_eq!(output, "0\n0\n120\n0\n");
+}
+
+#[test]
+fn io() {
+    let output = run("+n=42;
+$$main() {
+    do io::print(n);
+    do io::newline();
+    do io::println(n);
+    do io::show(\"n\" n);
+}");
+    assert_eq!(output, "42\n42\nn: 42\n");
+}
+
+#[test]
+fn use_without_flag() {
+    let result = Interpreter::new(default_opcodes())
+        .source("test.ill", "use std::math;\n+n;\n$$main() { dor math::fact(4) f; dsl f; }")
+        .run();
+    assert!(result.is_ok());
+    assert_eq!(result.output, "24\n");
+}
+
+#[test]
+fn preamble_registers_can_use_modules() {
+    let result = Interpreter::new(default_opcodes())
+        .preamble("pre.ill", "use std::logic;\n+yes=logic::TRUE;")
+        .source("test.ill", "+n;\n$$main() { dsl yes; }")
+        .run();
+    assert!(result.is_ok());
+    assert_eq!(result.output, "0\n");
+}
+
+#[test]
+fn unknown_module() {
+    match error("use std::nope;\n+n;\n$$main() { }") {
+        IllError::ModuleNotFound(_, ref name) if name == "std::nope" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn helpers_stay_private() {
+    match error("use std::math;\n+n;\n$$main() { do math::product(2); }") {
+        IllError::PrivateItem(_, ref name) if name == "math::product" => {}
+        other => panic!("unexpected error: {}", other),
+    }
+}